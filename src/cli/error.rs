@@ -0,0 +1,63 @@
+use crate::compare::error::CompareError;
+use crate::config::error::ConfigError;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub enum CliError {
+    /// The command line arguments couldn't be parsed.
+    Usage(String),
+    /// The arguments were fine, but loading the config or rendering failed.
+    Config(ConfigError),
+    /// Comparing two images failed.
+    Compare(CompareError),
+    /// The compared images differ more than allowed.
+    Threshold(String),
+}
+
+impl CliError {
+    /// The exit code the process should return when this error occurs.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 2,
+            CliError::Config(_) => 1,
+            CliError::Compare(_) => 1,
+            CliError::Threshold(_) => 3,
+        }
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(e) => write!(f, "{}", e),
+            CliError::Config(e) => write!(f, "{}", e),
+            CliError::Compare(e) => write!(f, "{}", e),
+            CliError::Threshold(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for CliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CliError::Usage(_) | CliError::Threshold(_) => None,
+            // The wrapped errors are displayed directly, so their source is ours.
+            CliError::Config(e) => e.source(),
+            CliError::Compare(e) => e.source(),
+        }
+    }
+}
+
+impl From<ConfigError> for CliError {
+    fn from(e: ConfigError) -> Self {
+        CliError::Config(e)
+    }
+}
+
+impl From<CompareError> for CliError {
+    fn from(e: CompareError) -> Self {
+        CliError::Compare(e)
+    }
+}
@@ -0,0 +1,229 @@
+use crate::cli::error::CliError;
+use crate::config::overrides::Override;
+use log::LevelFilter;
+use std::path::PathBuf;
+
+pub mod error;
+pub mod run;
+
+pub const USAGE: &str = "\
+Usage:
+    rusttracer [OPTIONS] <config>
+    rusttracer --dump-default <file>
+    rusttracer compare [OPTIONS] <reference> <image>
+
+Renders the scene described by the yml config file <config>.
+
+Options:
+    -s, --set <key>=<value>     Override a config field, for example
+                                `--set camera.width=500` or
+                                `--set raytracer.jmstracer.samples_per_pixel=10`.
+                                Can be given multiple times.
+    -c, --camera <name>         Render the camera called <name> from the camera
+                                file of the config. Can be given multiple times
+                                to render several cameras one after the other.
+    -l, --log-level <level>     One of off, error, warn, info, debug or trace.
+                                Defaults to info.
+        --dump-default <file>   Write the default config to <file> and exit.
+    -h, --help                  Print this message and exit.
+
+Compare prints the MSE, RMSE, PSNR and SSIM of <image> against <reference>.
+
+Compare options:
+        --diff <file>           Write a false color image of the differences to <file>.
+        --max-mse <value>       Exit with code 3 when the MSE is above <value>.
+        --min-ssim <value>      Exit with code 3 when the SSIM is below <value>.";
+
+#[derive(Debug)]
+pub enum Command {
+    /// Print the usage message.
+    Help,
+
+    /// Write the default config to a file.
+    DumpDefault { filename: PathBuf },
+
+    /// Render the scene described in a config file.
+    Render {
+        config: PathBuf,
+        overrides: Vec<Override>,
+        /// Names of cameras from the camera file, replacing the selection in the config.
+        cameras: Vec<String>,
+        log_level: LevelFilter,
+    },
+
+    /// Compare an image against a reference image.
+    Compare {
+        reference: PathBuf,
+        image: PathBuf,
+        diff: Option<PathBuf>,
+        max_mse: Option<f64>,
+        min_ssim: Option<f64>,
+    },
+}
+
+/// Parses the command line arguments (without the program name) into a `Command`.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, CliError> {
+    let mut args = args.into_iter().peekable();
+    if args.peek().map(String::as_str) == Some("compare") {
+        args.next();
+        return parse_compare(args);
+    }
+
+    let mut config = None;
+    let mut overrides = Vec::new();
+    let mut cameras = Vec::new();
+    let mut log_level = LevelFilter::Info;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| CliError::Usage(format!("missing value for {}", name)))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-s" | "--set" => overrides.push(value(&arg)?.parse()?),
+            "-c" | "--camera" => cameras.push(value(&arg)?),
+            "-l" | "--log-level" => {
+                let level = value(&arg)?;
+                log_level = level
+                    .parse()
+                    .map_err(|_| CliError::Usage(format!("unknown log level '{}'", level)))?;
+            }
+            "--dump-default" => {
+                return Ok(Command::DumpDefault {
+                    filename: value(&arg)?.into(),
+                })
+            }
+            _ if arg.starts_with('-') => {
+                return Err(CliError::Usage(format!("unknown option '{}'", arg)))
+            }
+            _ if config.is_none() => config = Some(PathBuf::from(arg)),
+            _ => return Err(CliError::Usage(format!("unexpected argument '{}'", arg))),
+        }
+    }
+
+    let config = config.ok_or_else(|| CliError::Usage("no config file given".to_string()))?;
+
+    Ok(Command::Render {
+        config,
+        overrides,
+        cameras,
+        log_level,
+    })
+}
+
+/// Parses the arguments following `compare`.
+fn parse_compare(args: impl Iterator<Item = String>) -> Result<Command, CliError> {
+    let mut args = args;
+
+    let mut images = Vec::new();
+    let mut diff = None;
+    let mut max_mse = None;
+    let mut min_ssim = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| CliError::Usage(format!("missing value for {}", name)))
+        };
+        let number = |name: &str, value: String| {
+            value
+                .parse::<f64>()
+                .map_err(|_| CliError::Usage(format!("invalid value for {}: '{}'", name, value)))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--diff" => diff = Some(value(&arg)?.into()),
+            "--max-mse" => max_mse = Some(number(&arg, value(&arg)?)?),
+            "--min-ssim" => min_ssim = Some(number(&arg, value(&arg)?)?),
+            _ if arg.starts_with('-') => {
+                return Err(CliError::Usage(format!("unknown option '{}'", arg)))
+            }
+            _ if images.len() < 2 => images.push(PathBuf::from(arg)),
+            _ => return Err(CliError::Usage(format!("unexpected argument '{}'", arg))),
+        }
+    }
+
+    if images.len() < 2 {
+        return Err(CliError::Usage(
+            "compare needs a reference image and an image".to_string(),
+        ));
+    }
+    let image = images.pop().unwrap();
+    let reference = images.pop().unwrap();
+
+    Ok(Command::Compare {
+        reference,
+        image,
+        diff,
+        max_mse,
+        min_ssim,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{parse, Command};
+    use log::LevelFilter;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_render() {
+        let command =
+            parse(args("scene.yml --set camera.width=10 -l debug -c front --camera top")).unwrap();
+
+        match command {
+            Command::Render {
+                config,
+                overrides,
+                cameras,
+                log_level,
+            } => {
+                assert_eq!(config.to_str(), Some("scene.yml"));
+                assert_eq!(overrides.len(), 1);
+                assert_eq!(cameras, ["front", "top"]);
+                assert_eq!(log_level, LevelFilter::Debug);
+            }
+            c => panic!("unexpected command {:?}", c),
+        }
+    }
+
+    #[test]
+    fn test_parse_compare() {
+        let command = parse(args("compare truth.bmp render.bmp --max-mse 12.5")).unwrap();
+
+        match command {
+            Command::Compare {
+                reference,
+                image,
+                diff,
+                max_mse,
+                min_ssim,
+            } => {
+                assert_eq!(reference.to_str(), Some("truth.bmp"));
+                assert_eq!(image.to_str(), Some("render.bmp"));
+                assert!(diff.is_none());
+                assert_eq!(max_mse, Some(12.5));
+                assert!(min_ssim.is_none());
+            }
+            c => panic!("unexpected command {:?}", c),
+        }
+
+        assert!(parse(args("compare truth.bmp")).is_err());
+        assert!(parse(args("compare a.bmp b.bmp --min-ssim high")).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(args("")).is_err());
+        assert!(parse(args("a.yml b.yml")).is_err());
+        assert!(parse(args("a.yml --set")).is_err());
+        assert!(parse(args("a.yml --log-level loud")).is_err());
+        assert!(parse(args("a.yml --frobnicate")).is_err());
+    }
+}
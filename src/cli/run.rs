@@ -0,0 +1,62 @@
+use crate::cli::error::CliError;
+use crate::cli::{Command, USAGE};
+use crate::compare;
+use crate::compare::error::CompareError;
+use crate::config::Config;
+
+impl Command {
+    pub fn run(self) -> Result<(), CliError> {
+        match self {
+            Command::Help => println!("{}", USAGE),
+            Command::DumpDefault { filename } => Config::default().dump(filename)?,
+            Command::Render {
+                config,
+                overrides,
+                cameras,
+                log_level,
+            } => {
+                simple_logging::log_to_stderr(log_level);
+
+                let mut config = Config::load_with_overrides(config, &overrides)?;
+                if !cameras.is_empty() {
+                    config.select_cameras(cameras)?;
+                }
+                config.run()?;
+            }
+            Command::Compare {
+                reference,
+                image,
+                diff,
+                max_mse,
+                min_ssim,
+            } => {
+                let reference = compare::load(reference)?;
+                let image = compare::load(image)?;
+
+                let comparison = compare::compare(&reference, &image)?;
+                println!("{}", comparison);
+
+                if let Some(diff) = diff {
+                    compare::difference_image(&reference, &image)?
+                        .save(&diff)
+                        .map_err(CompareError::save(&diff))?;
+                }
+
+                if let Some(max_mse) = max_mse.filter(|&max| comparison.mse > max) {
+                    return Err(CliError::Threshold(format!(
+                        "the MSE of {:.4} is above the maximum of {}",
+                        comparison.mse, max_mse
+                    )));
+                }
+                if let Some(min_ssim) = min_ssim.filter(|&min| comparison.ssim < min) {
+                    return Err(CliError::Threshold(format!(
+                        "the SSIM of {:.6} is below the minimum of {}",
+                        comparison.ssim, min_ssim
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use image::ImageError;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum CompareError {
+    OpenError {
+        path: PathBuf,
+        // Boxed because image errors are large
+        error: Box<ImageError>,
+    },
+    SaveError {
+        path: PathBuf,
+        error: Box<ImageError>,
+    },
+    SizeMismatch {
+        reference: (u32, u32),
+        image: (u32, u32),
+    },
+}
+
+impl Display for CompareError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CompareError::OpenError { path, .. } => {
+                write!(f, "couldn't open image '{}'", path.display())
+            }
+            CompareError::SaveError { path, .. } => {
+                write!(f, "couldn't save image '{}'", path.display())
+            }
+            CompareError::SizeMismatch { reference, image } => write!(
+                f,
+                "the images have different sizes ({}x{} and {}x{})",
+                reference.0, reference.1, image.0, image.1
+            ),
+        }
+    }
+}
+
+impl Error for CompareError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CompareError::OpenError { error, .. } => Some(error.as_ref()),
+            CompareError::SaveError { error, .. } => Some(error.as_ref()),
+            CompareError::SizeMismatch { .. } => None,
+        }
+    }
+}
+
+impl CompareError {
+    pub fn open(path: impl Into<PathBuf>) -> impl FnOnce(ImageError) -> Self {
+        let path = path.into();
+        move |error| CompareError::OpenError {
+            path,
+            error: Box::new(error),
+        }
+    }
+
+    pub fn save(path: impl Into<PathBuf>) -> impl FnOnce(ImageError) -> Self {
+        let path = path.into();
+        move |error| CompareError::SaveError {
+            path,
+            error: Box::new(error),
+        }
+    }
+}
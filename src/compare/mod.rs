@@ -0,0 +1,279 @@
+use crate::compare::error::CompareError;
+use image::{Rgb, RgbImage};
+use std::f64;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+pub mod error;
+
+/// The largest value of a color channel. All metrics are on this scale, so the
+/// numbers are comparable with the ones in validation/MSE/notes.
+const MAX_VALUE: f64 = 255.;
+
+/// Standard deviation of the gaussian window SSIM is computed over.
+const SSIM_SIGMA: f64 = 1.5;
+/// Number of pixels the gaussian window extends from its center (an 11x11 window).
+const SSIM_RADIUS: isize = 5;
+/// Constants that keep SSIM stable in flat areas, as given in the SSIM paper.
+const SSIM_C1: f64 = (0.01 * MAX_VALUE) * (0.01 * MAX_VALUE);
+const SSIM_C2: f64 = (0.03 * MAX_VALUE) * (0.03 * MAX_VALUE);
+
+/// How much an image differs from a reference image.
+#[derive(Debug)]
+pub struct Comparison {
+    /// Mean squared error over all color channels.
+    pub mse: f64,
+    /// Root mean squared error over all color channels.
+    pub rmse: f64,
+    /// Peak signal to noise ratio in dB. Infinite for identical images.
+    pub psnr: f64,
+    /// Mean structural similarity of the luminance. 1 for identical images.
+    pub ssim: f64,
+    /// The largest difference of a single color channel.
+    pub max_difference: u8,
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "MSE:            {:.4}", self.mse)?;
+        writeln!(f, "RMSE:           {:.4}", self.rmse)?;
+        writeln!(f, "PSNR:           {:.4} dB", self.psnr)?;
+        writeln!(f, "SSIM:           {:.6}", self.ssim)?;
+        write!(f, "Max difference: {}", self.max_difference)
+    }
+}
+
+/// Opens an image in any format the image crate supports.
+pub fn load(path: impl AsRef<Path>) -> Result<RgbImage, CompareError> {
+    let path = path.as_ref();
+    Ok(image::open(path)
+        .map_err(CompareError::open(path))?
+        .to_rgb8())
+}
+
+pub fn compare(reference: &RgbImage, image: &RgbImage) -> Result<Comparison, CompareError> {
+    check_size(reference, image)?;
+
+    let mse = mean_squared_error(reference, image);
+    let psnr = if mse > 0. {
+        10. * (MAX_VALUE * MAX_VALUE / mse).log10()
+    } else {
+        f64::INFINITY
+    };
+
+    Ok(Comparison {
+        mse,
+        rmse: mse.sqrt(),
+        psnr,
+        ssim: ssim(reference, image),
+        max_difference: reference
+            .pixels()
+            .zip(image.pixels())
+            .map(|(a, b)| pixel_difference(a, b))
+            .max()
+            .unwrap_or(0),
+    })
+}
+
+/// A false color image of where the images differ. Every pixel shows the largest
+/// difference of its color channels, going from black (equal) through red and
+/// yellow to white for the largest difference in the image.
+pub fn difference_image(reference: &RgbImage, image: &RgbImage) -> Result<RgbImage, CompareError> {
+    check_size(reference, image)?;
+
+    let differences: Vec<u8> = reference
+        .pixels()
+        .zip(image.pixels())
+        .map(|(a, b)| pixel_difference(a, b))
+        .collect();
+    let max = differences.iter().copied().max().unwrap_or(0).max(1);
+
+    Ok(RgbImage::from_fn(
+        reference.width(),
+        reference.height(),
+        |x, y| {
+            let index = (y * reference.width() + x) as usize;
+            heat(differences[index] as f64 / max as f64)
+        },
+    ))
+}
+
+fn check_size(reference: &RgbImage, image: &RgbImage) -> Result<(), CompareError> {
+    if reference.dimensions() != image.dimensions() {
+        return Err(CompareError::SizeMismatch {
+            reference: reference.dimensions(),
+            image: image.dimensions(),
+        });
+    }
+
+    Ok(())
+}
+
+fn pixel_difference(a: &Rgb<u8>, b: &Rgb<u8>) -> u8 {
+    a.0.iter()
+        .zip(b.0.iter())
+        .map(|(&a, &b)| a.abs_diff(b))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Maps 0..1 to black, red, yellow and white, each color taking a third of the range.
+fn heat(t: f64) -> Rgb<u8> {
+    let channel = |offset: f64| ((3. * t - offset).clamp(0., 1.) * MAX_VALUE) as u8;
+    Rgb([channel(0.), channel(1.), channel(2.)])
+}
+
+fn mean_squared_error(reference: &RgbImage, image: &RgbImage) -> f64 {
+    let values = reference.as_raw().len();
+    if values == 0 {
+        return 0.;
+    }
+
+    let sum: f64 = reference
+        .as_raw()
+        .iter()
+        .zip(image.as_raw().iter())
+        .map(|(&a, &b)| {
+            let difference = a as f64 - b as f64;
+            difference * difference
+        })
+        .sum();
+
+    sum / values as f64
+}
+
+/// The mean structural similarity of the luminance of two images
+/// (https://en.wikipedia.org/wiki/Structural_similarity), using an 11x11 gaussian window.
+fn ssim(reference: &RgbImage, image: &RgbImage) -> f64 {
+    let (width, height) = (reference.width() as usize, reference.height() as usize);
+    if width == 0 || height == 0 {
+        return 1.;
+    }
+
+    let x = luminance(reference);
+    let y = luminance(image);
+    let kernel = gaussian_kernel();
+    let blur = |values: Vec<f64>| gaussian_blur(&values, width, height, &kernel);
+
+    let mean_x = blur(x.clone());
+    let mean_y = blur(y.clone());
+    let mean_xx = blur(x.iter().map(|a| a * a).collect());
+    let mean_yy = blur(y.iter().map(|b| b * b).collect());
+    let mean_xy = blur(x.iter().zip(&y).map(|(a, b)| a * b).collect());
+
+    let total: f64 = (0..width * height)
+        .map(|i| {
+            let variance_x = mean_xx[i] - mean_x[i] * mean_x[i];
+            let variance_y = mean_yy[i] - mean_y[i] * mean_y[i];
+            let covariance = mean_xy[i] - mean_x[i] * mean_y[i];
+
+            ((2. * mean_x[i] * mean_y[i] + SSIM_C1) * (2. * covariance + SSIM_C2))
+                / ((mean_x[i] * mean_x[i] + mean_y[i] * mean_y[i] + SSIM_C1)
+                    * (variance_x + variance_y + SSIM_C2))
+        })
+        .sum();
+
+    total / (width * height) as f64
+}
+
+fn luminance(image: &RgbImage) -> Vec<f64> {
+    image
+        .pixels()
+        .map(|p| 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64)
+        .collect()
+}
+
+fn gaussian_kernel() -> Vec<f64> {
+    let kernel: Vec<f64> = (-SSIM_RADIUS..=SSIM_RADIUS)
+        .map(|i| (-((i * i) as f64) / (2. * SSIM_SIGMA * SSIM_SIGMA)).exp())
+        .collect();
+    let sum: f64 = kernel.iter().sum();
+
+    kernel.into_iter().map(|i| i / sum).collect()
+}
+
+/// Blurs horizontally and then vertically. Pixels outside the image repeat the edge.
+fn gaussian_blur(values: &[f64], width: usize, height: usize, kernel: &[f64]) -> Vec<f64> {
+    let sample = |center: usize, offset: usize, size: usize| {
+        (center + offset)
+            .saturating_sub(SSIM_RADIUS as usize)
+            .min(size - 1)
+    };
+
+    let mut horizontal = vec![0.; values.len()];
+    for y in 0..height {
+        for x in 0..width {
+            horizontal[y * width + x] = kernel
+                .iter()
+                .enumerate()
+                .map(|(i, k)| k * values[y * width + sample(x, i, width)])
+                .sum();
+        }
+    }
+
+    let mut result = vec![0.; values.len()];
+    for y in 0..height {
+        for x in 0..width {
+            result[y * width + x] = kernel
+                .iter()
+                .enumerate()
+                .map(|(i, k)| k * horizontal[sample(y, i, height) * width + x])
+                .sum();
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compare::{compare, difference_image};
+    use image::{Rgb, RgbImage};
+
+    fn gradient(offset: u8) -> RgbImage {
+        RgbImage::from_fn(20, 20, |x, y| {
+            Rgb([(x * 10) as u8 + offset, (y * 10) as u8 + offset, offset])
+        })
+    }
+
+    #[test]
+    fn test_identical() {
+        let comparison = compare(&gradient(0), &gradient(0)).unwrap();
+
+        assert_eq!(comparison.mse, 0.);
+        assert_eq!(comparison.psnr, f64::INFINITY);
+        assert!((comparison.ssim - 1.).abs() < 1e-9);
+        assert_eq!(comparison.max_difference, 0);
+    }
+
+    #[test]
+    fn test_offset() {
+        let comparison = compare(&gradient(0), &gradient(10)).unwrap();
+
+        assert!((comparison.mse - 100.).abs() < 1e-9);
+        assert!((comparison.rmse - 10.).abs() < 1e-9);
+        assert!((comparison.psnr - 28.1308).abs() < 1e-4);
+        assert!(comparison.ssim < 1.);
+        assert_eq!(comparison.max_difference, 10);
+    }
+
+    #[test]
+    fn test_size_mismatch() {
+        let small = RgbImage::new(10, 20);
+
+        assert!(compare(&gradient(0), &small).is_err());
+        assert!(difference_image(&gradient(0), &small).is_err());
+    }
+
+    #[test]
+    fn test_difference_image() {
+        let mut image = gradient(0);
+        image.put_pixel(3, 4, Rgb([255, 255, 255]));
+
+        let difference = difference_image(&gradient(0), &image).unwrap();
+
+        assert_eq!(*difference.get_pixel(3, 4), Rgb([255, 255, 255]));
+        assert_eq!(*difference.get_pixel(0, 0), Rgb([0, 0, 0]));
+    }
+}
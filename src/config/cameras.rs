@@ -0,0 +1,101 @@
+use crate::config::error::ConfigError;
+use crate::config::{CameraConfig, CamerasConfig};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The contents of a camera file: camera configs by name.
+pub type NamedCameras = BTreeMap<String, CameraConfig>;
+
+impl CamerasConfig {
+    /// Loads the camera file and returns the selected cameras, in the order they were selected.
+    pub fn load(&self) -> Result<Vec<(String, CameraConfig)>, ConfigError> {
+        let path = Path::new(&self.file);
+        let contents = fs::read(path).map_err(ConfigError::io(path))?;
+        let cameras: NamedCameras =
+            serde_yaml::from_slice(&contents).map_err(ConfigError::yaml(path))?;
+
+        self.select_from(cameras)
+    }
+
+    fn select_from(
+        &self,
+        mut cameras: NamedCameras,
+    ) -> Result<Vec<(String, CameraConfig)>, ConfigError> {
+        let mut selected = Vec::new();
+
+        for name in &self.select {
+            match cameras.remove(name) {
+                Some(camera) => selected.push((name.clone(), camera)),
+                None if selected.iter().any(|(i, _)| i == name) => {
+                    return Err(ConfigError::InvalidConfig(format!(
+                        "camera '{}' is selected twice",
+                        name
+                    )))
+                }
+                None => {
+                    let mut names: Vec<&str> = cameras
+                        .keys()
+                        .chain(selected.iter().map(|(i, _)| i))
+                        .map(String::as_str)
+                        .collect();
+                    names.sort_unstable();
+
+                    return Err(ConfigError::InvalidConfig(format!(
+                        "there is no camera '{}' in '{}', the cameras are: {}",
+                        name,
+                        self.file,
+                        names.join(", ")
+                    )));
+                }
+            }
+        }
+
+        Ok(selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::cameras::NamedCameras;
+    use crate::config::CamerasConfig;
+
+    const CAMERAS: &str = "
+front:
+  position: {x: 0, y: 1, z: 3}
+  direction: {x: 0, y: 0, z: -1}
+  width: 100
+  height: 100
+  fov: 60
+top:
+  position: {x: 0, y: 5, z: 0}
+  look_at: {x: 0, y: 0, z: 0}
+  up: {x: 0, y: 0, z: -1}
+  width: 200
+  height: 100
+  fov: 45
+";
+
+    fn select(names: &[&str]) -> CamerasConfig {
+        CamerasConfig {
+            file: "cameras.yml".to_string(),
+            select: names.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let cameras: NamedCameras = serde_yaml::from_str(CAMERAS).unwrap();
+
+        let selected = select(&["top", "front"]).select_from(cameras).unwrap();
+        let names: Vec<&str> = selected.iter().map(|(i, _)| i.as_str()).collect();
+        assert_eq!(names, ["top", "front"]);
+        assert_eq!(selected[0].1.width, 200);
+
+        let cameras: NamedCameras = serde_yaml::from_str(CAMERAS).unwrap();
+        assert!(select(&["side"]).select_from(cameras).is_err());
+
+        let cameras: NamedCameras = serde_yaml::from_str(CAMERAS).unwrap();
+        assert!(select(&["top", "top"]).select_from(cameras).is_err());
+    }
+}
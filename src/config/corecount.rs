@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 // allow because names here are converted to yml keys which I want lowercase
 #[allow(non_camel_case_types)]
 pub enum ThreadCount {
     /// use all cores
+    #[default]
     all,
 
     /// Number of threads to be used
@@ -25,9 +26,3 @@ impl ThreadCount {
         }
     }
 }
-
-impl Default for ThreadCount {
-    fn default() -> Self {
-        ThreadCount::all
-    }
-}
@@ -1,9 +1,27 @@
 use crate::config::{
-    CameraConfig, DatastructureConfig, GeneralConfig, GeneratorConfig, RaytracerConfig,
-    ShaderConfig,
+    CameraConfig, Config, DatastructureConfig, FilterConfig, GeneralConfig, GeneratorConfig,
+    OutputConfig, PostProcessorConfig, ProjectionConfig, RaytracerConfig, ShaderConfig,
 };
 use crate::util::vector::Vector;
 
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            general: GeneralConfig::default(),
+            camera: Some(CameraConfig::default()),
+            cameras: None,
+            generator: GeneratorConfig::default(),
+            raytracer: RaytracerConfig::default(),
+            shader: ShaderConfig::default(),
+            datastructure: DatastructureConfig::default(),
+            progressive: None,
+            motion: Vec::new(),
+            // The template suggests tone mapping, but configs without the key get none.
+            postprocessors: vec![PostProcessorConfig::aces, PostProcessorConfig::srgb],
+        }
+    }
+}
+
 impl Default for GeneralConfig {
     fn default() -> Self {
         Self {
@@ -11,6 +29,18 @@ impl Default for GeneralConfig {
             scenename: "test".to_string(),
             outputname: "render.bmp".to_string(),
             texturepath: "scenes".to_string(),
+            output: OutputConfig::default(),
+            stats: None,
+            seed: None,
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            jpeg_quality: 90,
+            png_16bit: false,
         }
     }
 }
@@ -19,37 +49,46 @@ impl Default for CameraConfig {
     fn default() -> Self {
         Self {
             position: Vector::default(),
-            direction: Vector::new(0.0,0.0,-1.0),
+            direction: Some(Vector::new(0.0,0.0,-1.0)),
+            look_at: None,
+            up: camera_up(),
+            roll: 0.,
             width: 1000,
             height: 1000,
             fov: 60.,
+            projection: ProjectionConfig::default(),
+            lens: None,
+            shutter: None,
         }
     }
 }
 
-impl Default for DatastructureConfig {
-    fn default() -> Self {
-        DatastructureConfig::kdtree
-    }
-}
-
 impl Default for ShaderConfig {
     fn default() -> Self {
         ShaderConfig::vmcshader {
             air_density: 0.3,
             particle_reflectivity: 0.4,
+            light_sampling: false,
+            min_depth: min_depth(),
+            max_depth: max_depth(),
         }
     }
 }
 
-impl Default for RaytracerConfig {
+impl Default for FilterConfig {
     fn default() -> Self {
-        RaytracerConfig::basic
+        FilterConfig::r#box { radius: 0.5 }
     }
 }
 
-impl Default for GeneratorConfig {
-    fn default() -> Self {
-        GeneratorConfig::basic
-    }
+pub fn camera_up() -> Vector {
+    Vector::new(0., 1., 0.)
+}
+
+pub fn min_depth() -> usize {
+    3
+}
+
+pub fn max_depth() -> usize {
+    32
 }
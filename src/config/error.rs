@@ -1,34 +1,106 @@
+use crate::output::error::OutputError;
 use crate::scene::error::SceneError;
-use std::io;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::{fmt, io};
 
 #[derive(Debug)]
 pub enum ConfigError {
-    YamlError(serde_yaml::Error),
-    IoError(io::Error),
-    TobjLoadError(tobj::LoadError),
-    SceneError(SceneError),
+    YamlError {
+        path: PathBuf,
+        error: serde_yaml::Error,
+    },
+    JsonError {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+    IoError {
+        path: PathBuf,
+        error: io::Error,
+    },
+    TobjLoadError {
+        path: PathBuf,
+        error: tobj::LoadError,
+    },
+    SceneError {
+        path: PathBuf,
+        error: SceneError,
+    },
+    OverrideError(String),
+    InvalidConfig(String),
+    OutputError(OutputError),
 }
 
-impl From<serde_yaml::Error> for ConfigError {
-    fn from(e: serde_yaml::Error) -> Self {
-        ConfigError::YamlError(e)
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::YamlError { path, .. } => {
+                write!(f, "couldn't parse config '{}'", path.display())
+            }
+            ConfigError::JsonError { path, .. } => {
+                write!(f, "couldn't write json to '{}'", path.display())
+            }
+            ConfigError::IoError { path, .. } => {
+                write!(f, "couldn't access '{}'", path.display())
+            }
+            ConfigError::TobjLoadError { path, .. } => {
+                write!(f, "couldn't load scene '{}'", path.display())
+            }
+            ConfigError::SceneError { path, .. } => {
+                write!(f, "couldn't build scene '{}'", path.display())
+            }
+            ConfigError::OverrideError(e) => write!(f, "invalid override: {}", e),
+            ConfigError::InvalidConfig(e) => write!(f, "invalid config: {}", e),
+            ConfigError::OutputError(e) => write!(f, "{}", e),
+        }
     }
 }
 
-impl From<io::Error> for ConfigError {
-    fn from(e: io::Error) -> Self {
-        ConfigError::IoError(e)
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::YamlError { error, .. } => Some(error),
+            ConfigError::JsonError { error, .. } => Some(error),
+            ConfigError::IoError { error, .. } => Some(error),
+            ConfigError::TobjLoadError { error, .. } => Some(error),
+            ConfigError::SceneError { error, .. } => Some(error),
+            ConfigError::OverrideError(_) | ConfigError::InvalidConfig(_) => None,
+            // The output error is displayed directly, so its source is ours.
+            ConfigError::OutputError(e) => e.source(),
+        }
     }
 }
 
-impl From<tobj::LoadError> for ConfigError {
-    fn from(e: tobj::LoadError) -> Self {
-        ConfigError::TobjLoadError(e)
+impl ConfigError {
+    pub fn yaml(path: impl Into<PathBuf>) -> impl FnOnce(serde_yaml::Error) -> Self {
+        let path = path.into();
+        move |error| ConfigError::YamlError { path, error }
+    }
+
+    pub fn json(path: impl Into<PathBuf>) -> impl FnOnce(serde_json::Error) -> Self {
+        let path = path.into();
+        move |error| ConfigError::JsonError { path, error }
+    }
+
+    pub fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |error| ConfigError::IoError { path, error }
+    }
+
+    pub fn tobj(path: impl Into<PathBuf>) -> impl FnOnce(tobj::LoadError) -> Self {
+        let path = path.into();
+        move |error| ConfigError::TobjLoadError { path, error }
+    }
+
+    pub fn scene(path: impl Into<PathBuf>) -> impl FnOnce(SceneError) -> Self {
+        let path = path.into();
+        move |error| ConfigError::SceneError { path, error }
     }
 }
 
-impl From<SceneError> for ConfigError {
-    fn from(e: SceneError) -> Self {
-        ConfigError::SceneError(e)
+impl From<OutputError> for ConfigError {
+    fn from(e: OutputError) -> Self {
+        ConfigError::OutputError(e)
     }
 }
@@ -1,13 +1,17 @@
 use crate::config::corecount::ThreadCount;
 use crate::config::error::ConfigError;
+use crate::config::overrides::Override;
 use crate::util::vector::Vector;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fs;
 use std::path::Path;
 
+pub mod cameras;
 pub mod corecount;
 pub mod defaults;
 pub mod error;
+pub mod overrides;
 pub mod run;
 
 #[derive(Serialize, Deserialize)]
@@ -15,31 +19,116 @@ pub mod run;
 #[allow(non_camel_case_types)]
 pub struct Config {
     general: GeneralConfig,
-    camera: CameraConfig,
+
+    /// The camera to render with. It can be left out when cameras are selected
+    /// from a camera file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    camera: Option<CameraConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cameras: Option<CamerasConfig>,
+
     generator: GeneratorConfig,
     raytracer: RaytracerConfig,
+    #[serde(deserialize_with = "deserialize_shader")]
     shader: ShaderConfig,
     datastructure: DatastructureConfig,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    progressive: Option<ProgressiveConfig>,
+
+    /// Meshes that move while the shutter of the camera is open.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    motion: Vec<MotionConfig>,
+
+    /// Steps applied to the rendered image, in order. None by default. They are skipped
+    /// for float formats, which store the linear colors.
+    #[serde(default)]
+    postprocessors: Vec<PostProcessorConfig>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 // allow because names here are converted to yml keys which I want lowercase
 #[allow(non_camel_case_types)]
 pub enum RaytracerConfig {
     /// Simple raytracing. Cast one ray per pixel
+    #[default]
     basic,
     /// Use a multisampling raytracer. Samples every pixel n times.
-    jmstracer { samples_per_pixel: usize },
+    jmstracer {
+        samples_per_pixel: usize,
+        #[serde(default)]
+        sampler: SamplerConfig,
+        #[serde(default)]
+        filter: FilterConfig,
+    },
     /// Use a multisampling raytracer that jitters (randomizes) the rays
     /// slightly. Samples every pixel n times.
-    mstracer { samples_per_pixel: usize },
+    mstracer {
+        samples_per_pixel: usize,
+        #[serde(default)]
+        sampler: SamplerConfig,
+        #[serde(default)]
+        filter: FilterConfig,
+    },
+    /// Use a jittering multisampling raytracer that samples every pixel until the
+    /// estimated error of its color is below max_error, with at least min_samples
+    /// and at most max_samples samples.
+    adaptive {
+        min_samples: usize,
+        max_samples: usize,
+        /// The standard error of the luminance of a pixel relative to the luminance.
+        /// A pixel whose samples all agree stops after 1 / max_error samples at the earliest.
+        max_error: f64,
+        #[serde(default)]
+        sampler: SamplerConfig,
+        #[serde(default)]
+        filter: FilterConfig,
+    },
 }
 
+/// How the samples around a pixel are weighted. Every sample counts for all pixels whose
+/// center is within the radius (in pixels) of it.
 #[derive(Serialize, Deserialize)]
 // allow because names here are converted to yml keys which I want lowercase
 #[allow(non_camel_case_types)]
+pub enum FilterConfig {
+    /// Every sample counts the same. A radius of 0.5 averages the samples in the pixel.
+    r#box { radius: f64 },
+    /// Weights fall off linearly to zero at the radius.
+    tent { radius: f64 },
+    /// A Gaussian with falloff alpha, shifted down to reach zero at the radius.
+    gaussian { radius: f64, alpha: f64 },
+    /// The Mitchell-Netravali cubic. b and c trade blurring against ringing.
+    mitchell { radius: f64, b: f64, c: f64 },
+    /// A windowed sinc with tau lobes.
+    lanczos { radius: f64, tau: f64 },
+}
+
+/// How the random numbers of the samples of a pixel are picked.
+#[derive(Serialize, Deserialize, Default)]
+// allow because names here are converted to yml keys which I want lowercase
+#[allow(non_camel_case_types)]
+pub enum SamplerConfig {
+    /// Independent random numbers.
+    #[default]
+    random,
+    /// Jittered stratification of every dimension.
+    stratified,
+    /// The Halton sequence with a random shift per pixel.
+    halton,
+    /// The Sobol sequence with a random digital shift per pixel.
+    sobol,
+    /// The Sobol sequence with Owen scrambling, which gives noise like blue noise.
+    owen,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+// allow because names here are converted to yml keys which I want lowercase
+#[allow(non_camel_case_types)]
 pub enum GeneratorConfig {
     /// Don't use any multithreading
+    #[default]
     basic,
 
     /// Make use of the crossbeam library to spawn threads.
@@ -65,11 +154,44 @@ pub struct GeneralConfig {
     /// Filename of the scene that will render
     scenename: String,
 
-    /// Filename of the generated bitmap
+    /// Filename of the generated image. The extension picks the format: bmp, png, jpg,
+    /// tga, or pfm, hdr and exr to keep the linear (unclamped) colors.
     outputname: String,
 
     /// Path to search for texture files
     texturepath: String,
+
+    /// Settings for writing the generated image
+    #[serde(default)]
+    output: OutputConfig,
+
+    /// Write the render statistics (timings and ray counts) to a file next to the
+    /// generated image, in this format. For example render.stats.yml for render.bmp.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stats: Option<StatsFormat>,
+
+    /// Seed for the random numbers. Renders with the same seed and config are identical,
+    /// whatever the generator and the number of threads. Random when left out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    /// Quality of jpeg images, from 1 (smallest file) to 100 (best quality)
+    jpeg_quality: u8,
+
+    /// Write png images with 16 bits per channel instead of 8
+    png_16bit: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+// allow because names here are converted to yml keys which I want lowercase
+#[allow(non_camel_case_types)]
+pub enum StatsFormat {
+    yml,
+    json,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -77,16 +199,160 @@ pub struct CameraConfig {
     /// The position of the camera in 3d space
     position: Vector,
 
-    /// The rotation of the camera in 3d space
-    direction: Vector,
+    /// The direction the camera looks in. Give either this or `look_at`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    direction: Option<Vector>,
+
+    /// The point the camera looks at. Give either this or `direction`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    look_at: Option<Vector>,
+
+    /// The direction that is up in the image. It doesn't have to be
+    /// perpendicular to the direction the camera looks in.
+    #[serde(default = "defaults::camera_up")]
+    up: Vector,
+
+    /// Rotation of the camera around the direction it looks in, in degrees.
+    /// Positive values turn the camera counter clockwise, so the image turns clockwise.
+    #[serde(default)]
+    roll: f64,
 
     /// The width of the image to be generated
     width: usize,
     /// The height of the image to be generated
     height: usize,
 
-    /// The field of view of the camera
+    /// The vertical field of view of the camera in degrees
     fov: f64,
+
+    /// How the scene is projected onto the image
+    #[serde(default)]
+    projection: ProjectionConfig,
+
+    /// Without a lens, everything in the image is sharp.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lens: Option<LensConfig>,
+
+    /// Without a shutter interval, moving meshes are rendered at time 0 without motion blur.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shutter: Option<ShutterConfig>,
+}
+
+/// The interval in which the shutter is open. Moving meshes are at their start
+/// transform at time 0 and at their end transform at time 1.
+#[derive(Serialize, Deserialize)]
+pub struct ShutterConfig {
+    open: f64,
+    close: f64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+// allow because names here are converted to yml keys which I want lowercase
+#[allow(non_camel_case_types)]
+pub enum ProjectionConfig {
+    /// A pinhole camera. Straight lines stay straight.
+    #[default]
+    perspective,
+    /// All rays are parallel, so sizes don't shrink with distance.
+    orthographic {
+        /// The height of the view in scene units. It replaces the field of view.
+        height: f64,
+    },
+    /// An equidistant fisheye. The field of view can go up to 360 degrees and is measured
+    /// across the image circle, which touches the top and bottom of the image. Everything
+    /// outside the circle is black.
+    fisheye,
+    /// A 360 degree panorama around the up vector. It ignores the field of view.
+    equirectangular,
+}
+
+/// Named cameras from a separate file, so a scene can be rendered from several
+/// predefined viewpoints.
+#[derive(Serialize, Deserialize)]
+pub struct CamerasConfig {
+    /// A yml file that maps camera names to cameras like the `camera` block.
+    file: String,
+
+    /// The names of the cameras to render, one after the other. Every image is saved
+    /// with the name of its camera before the extension, render.front.bmp for a camera
+    /// named front. When empty, the `camera` block is rendered instead.
+    #[serde(default)]
+    select: Vec<String>,
+}
+
+/// A thin lens, which gives depth of field.
+#[derive(Serialize, Deserialize)]
+pub struct LensConfig {
+    /// The radius of the aperture. The larger it is, the blurrier things
+    /// outside of the focus distance get.
+    aperture_radius: f64,
+
+    /// The distance from the camera, along its direction, at which things are sharp.
+    /// Fisheye and equirectangular cameras measure it along every ray instead.
+    focus_distance: f64,
+
+    /// The number of aperture blades, at least 3. Out of focus highlights get the shape of
+    /// a polygon with this many corners. Leave it out for a round aperture.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    blades: Option<usize>,
+}
+
+/// A mesh that moves from one transform to another during the exposure.
+#[derive(Serialize, Deserialize)]
+pub struct MotionConfig {
+    /// The name of the mesh (the OBJ object or group) that moves.
+    mesh: String,
+
+    /// Where the mesh is at time 0. It isn't moved when left out.
+    #[serde(default)]
+    start: TransformConfig,
+
+    /// Where the mesh is at time 1.
+    end: TransformConfig,
+}
+
+/// A rotation around the center of the mesh, followed by a translation.
+#[derive(Serialize, Deserialize, Default)]
+pub struct TransformConfig {
+    #[serde(default)]
+    translation: Vector,
+
+    /// In degrees around the x, y and z axis, applied in that order.
+    #[serde(default)]
+    rotation: Vector,
+}
+
+/// Render the image in multiple passes and write the image so far to disk in between,
+/// so long renders can be inspected and stopped early.
+#[derive(Serialize, Deserialize)]
+pub struct ProgressiveConfig {
+    /// The number of passes. Every pass renders every pixel with the raytracer,
+    /// so the final image has passes × samples_per_pixel samples per pixel.
+    passes: usize,
+
+    /// The minimum number of seconds between writing two intermediate images.
+    /// 0 writes the image after every pass.
+    #[serde(default)]
+    save_interval: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+// allow because names here are converted to yml keys which I want lowercase
+#[allow(non_camel_case_types)]
+pub enum PostProcessorConfig {
+    /// Raise every color channel to the power `exponent`.
+    gamma { exponent: f64 },
+    /// Brighten (or darken when negative) the image by this many stops.
+    /// Every stop doubles the brightness.
+    exposure { stops: f64 },
+    /// Reinhard tone mapping. Only infinitely bright colors become white.
+    reinhard,
+    /// Reinhard tone mapping where colors with a luminance of `white_point` become white.
+    reinhard_extended { white_point: f64 },
+    /// Filmic tone mapping with a fit of the ACES curve.
+    aces,
+    /// Encode the colors with the sRGB transfer function, which image viewers expect.
+    srgb,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -98,50 +364,183 @@ pub enum ShaderConfig {
 
     /// More advanced shader that uses monte carlo raytracing or pathtracing.
     /// (https://en.wikipedia.org/wiki/Path_tracing)
-    mcshader,
+    mcshader {
+        /// Sample the light sources directly at every bounce (next event estimation),
+        /// combined with the bounces through multiple importance sampling.
+        #[serde(default)]
+        light_sampling: bool,
+
+        /// Paths always bounce at least this many times. After that, Russian roulette
+        /// ends them at random based on how much light they still carry.
+        #[serde(default = "defaults::min_depth")]
+        min_depth: usize,
+        /// Paths never bounce more than this many times.
+        #[serde(default = "defaults::max_depth")]
+        max_depth: usize,
+    },
     vmcshader {
         air_density: f64,
         particle_reflectivity: f64,
+
+        /// Sample the light sources directly at every bounce (next event estimation),
+        /// combined with the bounces through multiple importance sampling.
+        #[serde(default)]
+        light_sampling: bool,
+
+        /// Paths always bounce at least this many times. After that, Russian roulette
+        /// ends them at random based on how much light they still carry.
+        #[serde(default = "defaults::min_depth")]
+        min_depth: usize,
+        /// Paths never bounce more than this many times.
+        #[serde(default = "defaults::max_depth")]
+        max_depth: usize,
     },
 }
 
-#[derive(Serialize, Deserialize)]
+/// Parses a shader like serde does, but shaders whose options all have defaults can also be
+/// given by just their name, like `shader: mcshader` from before the mcshader had options.
+fn deserialize_shader<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<ShaderConfig, D::Error> {
+    let value = serde_yaml::Value::deserialize(deserializer)?;
+
+    match ShaderConfig::deserialize(value.clone()) {
+        Err(error) if value.is_string() => {
+            let mut options = serde_yaml::Mapping::new();
+            options.insert(value, serde_yaml::Mapping::new().into());
+
+            ShaderConfig::deserialize(serde_yaml::Value::Mapping(options))
+                .map_err(|_| D::Error::custom(error))
+        }
+        result => result.map_err(D::Error::custom),
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
 // allow because names here are converted to yml keys which I want lowercase
 #[allow(non_camel_case_types)]
 pub enum DatastructureConfig {
     /// Don't use any datastructure. Just iterate through the triangles of the scene.
     basic,
     /// Use a kdtree as a datastructure to speed up rendering of large scenes.
+    #[default]
     kdtree,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            general: Default::default(),
-            camera: Default::default(),
+impl Config {
+    pub fn dump(&self, filename: impl AsRef<Path>) -> Result<(), ConfigError> {
+        let filename = filename.as_ref();
+        let yamlstring = serde_yaml::to_string(self).map_err(ConfigError::yaml(filename))?;
 
-            generator: Default::default(),
-            raytracer: Default::default(),
-            shader: Default::default(),
-            datastructure: Default::default(),
+        fs::write(filename, yamlstring).map_err(ConfigError::io(filename))?;
+
+        Ok(())
+    }
+
+    /// Renders the cameras called `names` from the camera file instead of the
+    /// cameras selected in the config.
+    pub fn select_cameras(&mut self, names: Vec<String>) -> Result<(), ConfigError> {
+        match &mut self.cameras {
+            Some(cameras) => {
+                cameras.select = names;
+                Ok(())
+            }
+            None => Err(ConfigError::InvalidConfig(
+                "cameras can only be selected by name when the config has a camera file"
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Loads a config file and applies `overrides` to it (in order) before it is parsed.
+    pub fn load_with_overrides(
+        filename: impl AsRef<Path>,
+        overrides: &[Override],
+    ) -> Result<Self, ConfigError> {
+        let filename = filename.as_ref();
+        let contents = fs::read(filename).map_err(ConfigError::io(filename))?;
+        let mut value: serde_yaml::Value =
+            serde_yaml::from_slice(&contents).map_err(ConfigError::yaml(filename))?;
+
+        for i in overrides {
+            i.apply(&mut value)?;
         }
+
+        serde_yaml::from_value(value).map_err(ConfigError::yaml(filename))
     }
 }
 
-impl Config {
-    #[allow(unused)]
-    pub fn dump(&self, filename: impl AsRef<Path>) -> Result<(), ConfigError> {
-        let yamlstring = serde_yaml::to_string(self)?;
+#[cfg(test)]
+mod tests {
+    use crate::config::{deserialize_shader, Config, PostProcessorConfig, ShaderConfig};
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Shader {
+        #[serde(deserialize_with = "deserialize_shader")]
+        shader: ShaderConfig,
+    }
 
-        fs::write(filename, yamlstring)?;
+    #[test]
+    fn test_shader_by_name() {
+        let parse = |yml| serde_yaml::from_str::<Shader>(yml).map(|i| i.shader);
 
-        Ok(())
+        match parse("shader: mcshader").unwrap() {
+            ShaderConfig::mcshader {
+                light_sampling,
+                min_depth,
+                max_depth,
+            } => assert_eq!((light_sampling, min_depth, max_depth), (false, 3, 32)),
+            _ => panic!("expected an mcshader"),
+        }
+
+        assert!(matches!(
+            parse("shader: mtlshader").unwrap(),
+            ShaderConfig::mtlshader
+        ));
+        assert!(matches!(
+            parse("shader:\n  mcshader:\n    light_sampling: true").unwrap(),
+            ShaderConfig::mcshader {
+                light_sampling: true,
+                ..
+            }
+        ));
+
+        // The vmcshader has options without defaults.
+        assert!(parse("shader: vmcshader").is_err());
+        assert!(parse("shader: pathtracer").is_err());
+    }
+
+    #[test]
+    fn test_tone_mapping_chain() {
+        let yml = "- exposure:\n    stops: -1.5\n- reinhard_extended:\n    white_point: 4\n- srgb";
+        let chain: Vec<PostProcessorConfig> = serde_yaml::from_str(yml).unwrap();
+
+        assert!(matches!(
+            chain.as_slice(),
+            [
+                PostProcessorConfig::exposure { stops },
+                PostProcessorConfig::reinhard_extended { white_point },
+                PostProcessorConfig::srgb,
+            ] if *stops == -1.5 && *white_point == 4.
+        ));
     }
 
-    pub fn load(filename: impl AsRef<Path>) -> Result<Self, ConfigError> {
-        let contents = fs::read(filename)?;
+    #[test]
+    fn test_default_postprocessors() {
+        let mut yml = serde_yaml::to_value(Config::default()).unwrap();
+        yml.as_mapping_mut()
+            .unwrap()
+            .remove(&"postprocessors".into())
+            .unwrap();
+        let config: Config = serde_yaml::from_value(yml).unwrap();
+
+        assert!(config.postprocessors.is_empty());
 
-        Ok(serde_yaml::from_slice(&contents)?)
+        // The template still suggests tone mapping.
+        assert!(matches!(
+            Config::default().postprocessors.as_slice(),
+            [PostProcessorConfig::aces, PostProcessorConfig::srgb]
+        ));
     }
 }
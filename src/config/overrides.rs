@@ -0,0 +1,140 @@
+use crate::config::error::ConfigError;
+use serde_yaml::{Mapping, Value};
+use std::str::FromStr;
+
+/// A single `key=value` override of a config field, as given on the command line.
+///
+/// The key is a dot separated path into the yml config (for example `camera.width`
+/// or `raytracer.jmstracer.samples_per_pixel`). The value is parsed as yml, so numbers,
+/// strings and even complete sub-configs (`raytracer={mstracer: {samples_per_pixel: 5}}`)
+/// can be given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    path: Vec<String>,
+    value: Value,
+}
+
+impl Override {
+    pub fn new(key: &str, value: &str) -> Result<Self, ConfigError> {
+        let path: Vec<String> = key.split('.').map(|i| i.trim().to_string()).collect();
+        if path.iter().any(|i| i.is_empty()) {
+            return Err(ConfigError::OverrideError(format!("invalid key '{}'", key)));
+        }
+
+        let value = serde_yaml::from_str(value).map_err(|e| {
+            ConfigError::OverrideError(format!("invalid value for '{}': {}", key, e))
+        })?;
+
+        Ok(Self { path, value })
+    }
+
+    /// Applies this override to a parsed config. Mappings along the path that
+    /// don't exist yet are created.
+    pub fn apply(&self, config: &mut Value) -> Result<(), ConfigError> {
+        let mut current = config;
+
+        for (index, key) in self.path.iter().enumerate() {
+            if !current.is_mapping() {
+                if index == 0 {
+                    return Err(ConfigError::OverrideError(
+                        "the config is not a yml mapping".to_string(),
+                    ));
+                }
+                // A unit variant such as `raytracer: basic` is replaced by a mapping
+                // so a variant with fields can be selected.
+                *current = Value::Mapping(Mapping::new());
+            }
+
+            // Can't fail because non-mappings were replaced above.
+            let mapping = current.as_mapping_mut().unwrap();
+
+            let key = Value::String(key.clone());
+            if index == self.path.len() - 1 {
+                mapping.insert(key, self.value.clone());
+                return Ok(());
+            }
+
+            if !mapping.contains_key(&key) {
+                mapping.insert(key.clone(), Value::Mapping(Mapping::new()));
+            }
+            current = mapping.get_mut(&key).unwrap();
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Override {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '=');
+        let key = parts.next().unwrap_or_default();
+        let value = parts.next().ok_or_else(|| {
+            ConfigError::OverrideError(format!("expected key=value, got '{}'", s))
+        })?;
+
+        Self::new(key, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::overrides::Override;
+    use serde_yaml::Value;
+
+    fn config() -> Value {
+        serde_yaml::from_str(
+            "general:\n  outputname: render.bmp\ncamera:\n  width: 1000\nraytracer: basic\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_override_existing() {
+        let mut value = config();
+        "camera.width=200"
+            .parse::<Override>()
+            .unwrap()
+            .apply(&mut value)
+            .unwrap();
+
+        assert_eq!(value["camera"]["width"], Value::Number(200.into()));
+    }
+
+    #[test]
+    fn test_override_string() {
+        let mut value = config();
+        "general.outputname=out.bmp"
+            .parse::<Override>()
+            .unwrap()
+            .apply(&mut value)
+            .unwrap();
+
+        assert_eq!(
+            value["general"]["outputname"],
+            Value::String("out.bmp".into())
+        );
+    }
+
+    #[test]
+    fn test_override_replaces_unit_variant() {
+        let mut value = config();
+        "raytracer.jmstracer.samples_per_pixel=10"
+            .parse::<Override>()
+            .unwrap()
+            .apply(&mut value)
+            .unwrap();
+
+        assert_eq!(
+            value["raytracer"]["jmstracer"]["samples_per_pixel"],
+            Value::Number(10.into())
+        );
+    }
+
+    #[test]
+    fn test_invalid_override() {
+        assert!("camera.width".parse::<Override>().is_err());
+        assert!("camera..width=5".parse::<Override>().is_err());
+    }
+}
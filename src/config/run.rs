@@ -1,32 +1,90 @@
 use crate::config::error::ConfigError;
-use crate::config::{Config, DatastructureConfig, GeneratorConfig, RaytracerConfig, ShaderConfig};
+use crate::config::{
+    CameraConfig, Config, DatastructureConfig, FilterConfig, GeneratorConfig, PostProcessorConfig,
+    ProjectionConfig, RaytracerConfig, SamplerConfig, ShaderConfig, StatsFormat, TransformConfig,
+};
 use crate::datastructure::basic::BasicDataStructure;
 use crate::datastructure::bvh::KDTreeDataStructure;
 use crate::datastructure::DataStructure;
+use crate::filter::boxfilter::BoxFilter;
+use crate::filter::gaussian::Gaussian;
+use crate::filter::lanczos::Lanczos;
+use crate::filter::mitchell::Mitchell;
+use crate::filter::tent::Tent;
+use crate::filter::Filter;
 use crate::generator::basic::BasicGenerator;
 use crate::generator::crossbeam::CrossbeamGenerator;
 use crate::generator::rayon::RayonGenerator;
 use crate::generator::Generator;
+use crate::raytracer::adaptive::AdaptiveTracer;
 use crate::raytracer::basic::BasicRaytracer;
 use crate::raytracer::jmstracer::JMSTracer;
 use crate::raytracer::mstracer::MSTracer;
 use crate::raytracer::RayTracer;
+use crate::output::{ImageFormat, OutputOptions};
+use crate::postprocessors::aces::Aces;
+use crate::postprocessors::exposure::Exposure;
+use crate::postprocessors::gamma::Gamma;
+use crate::postprocessors::group::PostProcessorGroup;
+use crate::postprocessors::reinhard::Reinhard;
+use crate::postprocessors::srgb::Srgb;
+use crate::postprocessors::PostProcessor;
 use crate::renderer::RendererBuilder;
+use crate::sampler::halton::Halton;
+use crate::sampler::random::RandomSampler;
+use crate::sampler::sobol::Sobol;
+use crate::sampler::stratified::Stratified;
+use crate::sampler::Sampler;
+use crate::scene::motion::Transform;
 use crate::scene::SceneBuilder;
 use crate::shader::mcshader::McShader;
 use crate::shader::mtlshader::MtlShader;
+use crate::shader::roulette::RussianRoulette;
 use crate::shader::vmcshader::VMcShader;
 use crate::shader::Shader;
-use crate::util::camera::Camera;
+use crate::util::camera::{Camera, Projection, Shutter, ThinLens};
+use crate::util::outputbuffer::OutputBuffer;
+use crate::util::statistics::{reset_ray_counts, RenderStatistics};
+use log::{debug, info, warn};
+use std::fs;
 use std::path::Path;
+use std::time::{Duration, Instant};
 
 impl Config {
     pub fn run(self) -> Result<(), ConfigError> {
-        let tobj = tobj::load_obj(self.general.scenename.as_ref())?;
+        // Fail before rendering when the image couldn't be saved anyway.
+        let format = ImageFormat::from_path(&self.general.outputname)?;
+        let output_options = OutputOptions {
+            jpeg_quality: self.general.output.jpeg_quality,
+            png_16bit: self.general.output.png_16bit,
+        };
+
+        let cameras = self.cameras()?;
+
+        if let Some(progressive) = &self.progressive {
+            if progressive.passes == 0 {
+                return Err(ConfigError::InvalidConfig(
+                    "a progressive render needs at least 1 pass".to_string(),
+                ));
+            }
+        }
+
+        let scene_load_start = Instant::now();
 
-        let scene = SceneBuilder::new()
-            .texturepath(Path::new(&self.general.texturepath))
-            .build_from_tobj(tobj)?;
+        let scenename = Path::new(&self.general.scenename);
+        let tobj = tobj::load_obj(scenename).map_err(ConfigError::tobj(scenename))?;
+
+        let mut scenebuilder =
+            SceneBuilder::new().texturepath(Path::new(&self.general.texturepath));
+        for i in &self.motion {
+            scenebuilder = scenebuilder.motion(&i.mesh, i.start.build(), i.end.build());
+        }
+
+        let scene = scenebuilder
+            .build_from_tobj(tobj)
+            .map_err(ConfigError::scene(scenename))?;
+
+        let scene_load_time = scene_load_start.elapsed();
 
         let generator: Box<dyn Generator> = match self.generator {
             GeneratorConfig::basic => Box::new(BasicGenerator),
@@ -40,48 +98,401 @@ impl Config {
 
         let raytracer: Box<dyn RayTracer> = match self.raytracer {
             RaytracerConfig::basic => Box::new(BasicRaytracer),
-            RaytracerConfig::jmstracer { samples_per_pixel } => {
-                Box::new(JMSTracer::new(samples_per_pixel))
-            }
-            RaytracerConfig::mstracer { samples_per_pixel } => {
-                Box::new(MSTracer::new(samples_per_pixel))
+            RaytracerConfig::jmstracer {
+                samples_per_pixel,
+                ref sampler,
+                ref filter,
+            } => Box::new(JMSTracer::new(
+                samples_per_pixel,
+                sampler.build(),
+                filter.build()?,
+            )),
+            RaytracerConfig::mstracer {
+                samples_per_pixel,
+                ref sampler,
+                ref filter,
+            } => Box::new(MSTracer::new(
+                samples_per_pixel,
+                sampler.build(),
+                filter.build()?,
+            )),
+            RaytracerConfig::adaptive {
+                min_samples,
+                max_samples,
+                max_error,
+                ref sampler,
+                ref filter,
+            } => {
+                if min_samples > max_samples {
+                    return Err(ConfigError::InvalidConfig(format!(
+                        "the adaptive raytracer has more min_samples ({}) than max_samples ({})",
+                        min_samples, max_samples
+                    )));
+                }
+
+                Box::new(AdaptiveTracer::new(
+                    min_samples,
+                    max_samples,
+                    max_error,
+                    sampler.build(),
+                    filter.build()?,
+                ))
             }
         };
 
         let shader: Box<dyn Shader> = match self.shader {
             ShaderConfig::mtlshader => Box::new(MtlShader),
-            ShaderConfig::mcshader => Box::new(McShader),
+            ShaderConfig::mcshader {
+                light_sampling,
+                min_depth,
+                max_depth,
+            } => Box::new(McShader::new(
+                light_sampling,
+                RussianRoulette::new(min_depth, max_depth),
+            )),
             ShaderConfig::vmcshader {
                 air_density,
                 particle_reflectivity,
-            } => Box::new(VMcShader::new(air_density, particle_reflectivity)),
+                light_sampling,
+                min_depth,
+                max_depth,
+            } => Box::new(VMcShader::new(
+                air_density,
+                particle_reflectivity,
+                light_sampling,
+                RussianRoulette::new(min_depth, max_depth),
+            )),
         };
 
+        let datastructure_build_start = Instant::now();
         let datastructure: Box<dyn DataStructure> = match self.datastructure {
             DatastructureConfig::basic => Box::new(BasicDataStructure::new(&scene)),
             DatastructureConfig::kdtree => Box::new(KDTreeDataStructure::new(&scene)),
         };
+        let datastructure_build_time = datastructure_build_start.elapsed();
 
-        let renderer = RendererBuilder::new(generator.as_ref())
+        // Float formats are meant to store the linear colors, before any tone mapping.
+        let postprocessors: Vec<Box<dyn PostProcessor>> = if format.is_float() {
+            if !self.postprocessors.is_empty() {
+                warn!(
+                    "Not applying the postprocessors, {} stores the linear colors",
+                    self.general.outputname
+                );
+            }
+            Vec::new()
+        } else {
+            self.postprocessors
+                .iter()
+                .map(PostProcessorConfig::build)
+                .collect()
+        };
+        let mut postprocessor = PostProcessorGroup::new();
+        for i in &postprocessors {
+            postprocessor.add_postprocessor(i.as_ref());
+        }
+
+        let builder = RendererBuilder::new(generator.as_ref())
             .with_raytracer(raytracer.as_ref())
             .with_shader(shader.as_ref())
-            .with_datastructure(datastructure.as_ref())
-            .without_postprocessor();
+            .with_datastructure(datastructure.as_ref());
+        let renderer = if postprocessors.is_empty() {
+            builder.without_postprocessor()
+        } else {
+            builder.with_postprocessor(&postprocessor)
+        }
+        .with_seed(self.general.seed);
+
+        debug!("{:?}", renderer);
+
+        for (name, camera) in &cameras {
+            let outputname = match name {
+                Some(name) => {
+                    info!("Rendering camera '{}'", name);
+                    with_suffix(&self.general.outputname, name)
+                }
+                None => self.general.outputname.clone(),
+            };
+
+            reset_ray_counts();
+            let render_start = Instant::now();
 
-        let camera = Camera::new(
-            self.camera.position,
-            self.camera.direction,
-            self.camera.width,
-            self.camera.height,
-            self.camera.fov,
-        );
+            let output = match &self.progressive {
+                None => renderer.render(camera),
+                Some(progressive) => {
+                    let interval = Duration::from_secs_f64(progressive.save_interval.max(0.));
+                    let mut last_save = Instant::now();
 
-        dbg!(&renderer);
+                    renderer.render_progressive(camera, progressive.passes, &mut |progress| {
+                        info!("Finished pass {}/{}", progress.passes_done, progress.passes);
 
-        renderer.render(&camera)
-            .to_bmp()
-            .save(self.general.outputname)?;
+                        // The last pass is saved below like a normal render.
+                        if progress.is_last() || last_save.elapsed() < interval {
+                            return;
+                        }
+
+                        // Not fatal, the next pass or the final image may still be saved.
+                        match save_intermediate(&progress.image(), &outputname, &output_options) {
+                            Ok(()) => debug!("Saved the image after pass {}", progress.passes_done),
+                            Err(e) => warn!("Couldn't save pass {}: {}", progress.passes_done, e),
+                        }
+                        last_save = Instant::now();
+                    })
+                }
+            };
+
+            let statistics = RenderStatistics::new(
+                scene_load_time,
+                datastructure_build_time,
+                render_start.elapsed(),
+            );
+            println!("{}", statistics);
+
+            output.save(&outputname, &output_options)?;
+
+            if let Some(format) = self.general.stats {
+                save_statistics(&statistics, format, &outputname)?;
+            }
+        }
 
         Ok(())
     }
+
+    /// Builds the cameras to render: the selected cameras from the camera file with their
+    /// names, or the camera from the config when none are selected.
+    fn cameras(&self) -> Result<Vec<(Option<String>, Camera)>, ConfigError> {
+        let named = match &self.cameras {
+            Some(cameras) => cameras.load()?,
+            None => Vec::new(),
+        };
+
+        if !named.is_empty() {
+            if self.camera.is_some() {
+                debug!("Cameras are selected from the camera file, ignoring the camera block");
+            }
+
+            return named
+                .into_iter()
+                .map(|(name, camera)| Ok((Some(name), camera.build()?)))
+                .collect();
+        }
+
+        match &self.camera {
+            Some(camera) => Ok(vec![(None, camera.build()?)]),
+            None => Err(ConfigError::InvalidConfig(
+                "there is no camera, add a camera block or select cameras from a camera file"
+                    .to_string(),
+            )),
+        }
+    }
+}
+
+impl CameraConfig {
+    fn build(&self) -> Result<Camera, ConfigError> {
+        let mut camera = match (self.direction, self.look_at) {
+            (Some(direction), None) => Camera::new(
+                self.position,
+                direction,
+                self.up,
+                self.width,
+                self.height,
+                self.fov,
+            ),
+            (None, Some(target)) => Camera::look_at(
+                self.position,
+                target,
+                self.up,
+                self.width,
+                self.height,
+                self.fov,
+            ),
+            (Some(_), Some(_)) => {
+                return Err(ConfigError::InvalidConfig(
+                    "the camera has both a direction and a look_at point, give only one"
+                        .to_string(),
+                ))
+            }
+            (None, None) => {
+                return Err(ConfigError::InvalidConfig(
+                    "the camera needs either a direction or a look_at point".to_string(),
+                ))
+            }
+        }
+        .with_roll(self.roll)
+        .with_projection(match self.projection {
+            ProjectionConfig::perspective => Projection::Perspective,
+            ProjectionConfig::orthographic { height } => Projection::Orthographic { height },
+            ProjectionConfig::fisheye => Projection::Fisheye,
+            ProjectionConfig::equirectangular => Projection::Equirectangular,
+        });
+
+        if let Some(shutter) = &self.shutter {
+            camera = camera.with_shutter(Shutter {
+                open: shutter.open,
+                close: shutter.close,
+            });
+        }
+
+        if let Some(lens) = &self.lens {
+            camera = camera.with_lens(ThinLens {
+                aperture_radius: lens.aperture_radius,
+                focus_distance: lens.focus_distance,
+                blades: lens.blades,
+            });
+        }
+
+        Ok(camera)
+    }
+}
+
+impl TransformConfig {
+    fn build(&self) -> Transform {
+        Transform {
+            translation: self.translation,
+            rotation: self.rotation,
+        }
+    }
+}
+
+impl SamplerConfig {
+    fn build(&self) -> Box<dyn Sampler> {
+        match self {
+            SamplerConfig::random => Box::new(RandomSampler),
+            SamplerConfig::stratified => Box::new(Stratified),
+            SamplerConfig::halton => Box::new(Halton),
+            SamplerConfig::sobol => Box::new(Sobol::shifted()),
+            SamplerConfig::owen => Box::new(Sobol::owen()),
+        }
+    }
+}
+
+impl FilterConfig {
+    fn build(&self) -> Result<Box<dyn Filter>, ConfigError> {
+        let radius = match *self {
+            FilterConfig::r#box { radius }
+            | FilterConfig::tent { radius }
+            | FilterConfig::gaussian { radius, .. }
+            | FilterConfig::mitchell { radius, .. }
+            | FilterConfig::lanczos { radius, .. } => radius,
+        };
+        // A filter without a radius gives every sample a weight of 0, and a black image.
+        if !(radius > 0. && radius.is_finite()) {
+            return Err(ConfigError::InvalidConfig(format!(
+                "the radius of a filter must be positive, not {}",
+                radius
+            )));
+        }
+        if let FilterConfig::lanczos { tau, .. } = *self {
+            if tau <= 0. {
+                return Err(ConfigError::InvalidConfig(format!(
+                    "a lanczos filter needs a positive tau, not {}",
+                    tau
+                )));
+            }
+        }
+
+        Ok(match *self {
+            FilterConfig::r#box { radius } => Box::new(BoxFilter::new(radius)),
+            FilterConfig::tent { radius } => Box::new(Tent::new(radius)),
+            FilterConfig::gaussian { radius, alpha } => Box::new(Gaussian::new(radius, alpha)),
+            FilterConfig::mitchell { radius, b, c } => Box::new(Mitchell::new(radius, b, c)),
+            FilterConfig::lanczos { radius, tau } => Box::new(Lanczos::new(radius, tau)),
+        })
+    }
+}
+
+impl PostProcessorConfig {
+    fn build(&self) -> Box<dyn PostProcessor> {
+        match *self {
+            PostProcessorConfig::gamma { exponent } => Box::new(Gamma::new(exponent)),
+            PostProcessorConfig::exposure { stops } => Box::new(Exposure::new(stops)),
+            PostProcessorConfig::reinhard => Box::new(Reinhard::simple()),
+            PostProcessorConfig::reinhard_extended { white_point } => {
+                Box::new(Reinhard::extended(white_point))
+            }
+            PostProcessorConfig::aces => Box::new(Aces),
+            PostProcessorConfig::srgb => Box::new(Srgb),
+        }
+    }
+}
+
+/// Inserts `suffix` before the extension, so the format stays the same:
+/// render.bmp with suffix front becomes render.front.bmp.
+fn with_suffix(filename: &str, suffix: &str) -> String {
+    let path = Path::new(filename);
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// Writes the statistics next to the output image: render.bmp gets render.stats.yml (or .json).
+fn save_statistics(
+    statistics: &RenderStatistics,
+    format: StatsFormat,
+    outputname: &str,
+) -> Result<(), ConfigError> {
+    let path = match format {
+        StatsFormat::yml => Path::new(outputname).with_extension("stats.yml"),
+        StatsFormat::json => Path::new(outputname).with_extension("stats.json"),
+    };
+
+    let contents = match format {
+        StatsFormat::yml => serde_yaml::to_string(statistics).map_err(ConfigError::yaml(&path))?,
+        StatsFormat::json => {
+            serde_json::to_string_pretty(statistics).map_err(ConfigError::json(&path))?
+        }
+    };
+
+    fs::write(&path, contents).map_err(ConfigError::io(&path))
+}
+
+/// Saves an image that will be overwritten later. It's written to a temporary file first,
+/// so the file at `filename` is never half written when the render is stopped.
+fn save_intermediate(
+    output: &OutputBuffer,
+    filename: &str,
+    options: &OutputOptions,
+) -> Result<(), ConfigError> {
+    // render.bmp is written to render.part.bmp first.
+    let path = Path::new(filename);
+    let temppath = with_suffix(filename, "part");
+
+    output.save(&temppath, options)?;
+    fs::rename(&temppath, path).map_err(ConfigError::io(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::run::with_suffix;
+    use crate::config::FilterConfig;
+
+    #[test]
+    fn test_with_suffix() {
+        assert_eq!(with_suffix("render.bmp", "front"), "render.front.bmp");
+        assert_eq!(with_suffix("out/render.exr", "part"), "out/render.part.exr");
+        assert_eq!(with_suffix("render", "front"), "render.front");
+    }
+
+    #[test]
+    fn test_filter_build() {
+        assert!(FilterConfig::tent { radius: 1. }.build().is_ok());
+        assert!(FilterConfig::r#box { radius: 0. }.build().is_err());
+        assert!(FilterConfig::gaussian {
+            radius: -1.,
+            alpha: 2.
+        }
+        .build()
+        .is_err());
+        assert!(FilterConfig::lanczos {
+            radius: 3.,
+            tau: 0.
+        }
+        .build()
+        .is_err());
+    }
 }
@@ -21,25 +21,26 @@ impl<'d> BasicDataStructure<'d> {
         ray: &'a Ray,
         triangle: &'a Triangle,
     ) -> Option<Intersection<'a>> {
-        let edge1 = triangle.b() - triangle.a();
-        let edge2 = triangle.c() - triangle.a();
+        let [a, b, c] = triangle.vertices_at(ray.time);
+        let edge1 = b - a;
+        let edge2 = c - a;
 
         let h = ray.direction.cross(edge2);
-        let a = edge1.dot(h);
+        let det = edge1.dot(h);
 
-        if -INTERSECTION_EPSILON < a && a < INTERSECTION_EPSILON {
+        if -INTERSECTION_EPSILON < det && det < INTERSECTION_EPSILON {
             return None;
         }
 
-        let f = 1f64 / a;
+        let f = 1f64 / det;
 
-        let s = ray.origin - triangle.a();
+        let s = ray.origin - a;
         let u = f * s.dot(h);
 
         let q = s.cross(edge1);
         let v = f * ray.direction.dot(q);
 
-        if u < 0f64 || u > 1f64 {
+        if !(0f64..=1f64).contains(&u) {
             return None;
         }
 
@@ -66,7 +67,7 @@ impl<'d> DataStructure for BasicDataStructure<'d> {
         let mut min = None;
 
         for triangle in self.data.triangles() {
-            if let Some(intersection) = self.intersects_triangle(ray, &triangle) {
+            if let Some(intersection) = self.intersects_triangle(ray, triangle) {
                 min = match min {
                     None => Some(intersection),
                     Some(i) if intersection.t < i.t => Some(intersection),
@@ -48,15 +48,30 @@ impl BoundingBox {
         },
     };
 
+    #[cfg(test)]
     pub fn new(min: Vector, max: Vector) -> Self {
         Self { min, max }
     }
 
+    /// The box around everywhere the triangle goes while its mesh moves, padded by 0.01
+    /// on every side so that flat triangles don't get a flat box.
     pub fn from_triangle(triangle: &Triangle) -> Self {
-        Self::EMPTY
-            .include_point(triangle.a())
-            .include_point(triangle.b())
-            .include_point(triangle.c())
+        Self::swept(triangle).padded(0.01)
+    }
+
+    /// The box around everywhere the triangle goes while its mesh moves.
+    fn swept(triangle: &Triangle) -> Self {
+        let (vertices, margin) = triangle.swept_vertices();
+        vertices
+            .fold(Self::EMPTY, |bb, i| bb.include_point(i))
+            .padded(margin)
+    }
+
+    fn padded(&self, padding: f64) -> Self {
+        Self {
+            min: self.min - Vector::repeated(padding),
+            max: self.max + Vector::repeated(padding),
+        }
     }
 
     pub fn merge(&self, other: &Self) -> Self {
@@ -65,8 +80,8 @@ impl BoundingBox {
 
     pub fn include_point(&self, point: Vector) -> Self {
         Self {
-            min: self.min.min(&(point - Vector::repeated(0.01))),
-            max: self.max.max(&(point + Vector::repeated(0.01))),
+            min: self.min.min(&point),
+            max: self.max.max(&point),
         }
     }
 
@@ -102,23 +117,14 @@ impl BoundingBox {
 
     //Todo optimize contains function to get rid of false positives
     pub fn contains(&self, triangle: &Triangle) -> bool {
-        if triangle.a().x < self.min.x && triangle.b().x < self.min.x && triangle.c().x < self.min.x {
-            return false;
-        }
-        if triangle.a().y < self.min.y && triangle.b().y < self.min.y && triangle.c().y < self.min.y {
-            return false;
-        }
-        if triangle.a().z < self.min.z && triangle.b().z < self.min.z && triangle.c().z < self.min.z {
-            return false;
-        }
+        // A moving triangle is in the box if it is at any point during its motion.
+        let swept = Self::swept(triangle);
 
-        if triangle.a().x > self.max.x && triangle.b().x > self.max.x && triangle.c().x > self.max.x {
+        if swept.max.x < self.min.x || swept.max.y < self.min.y || swept.max.z < self.min.z {
             return false;
         }
-        if triangle.a().y > self.max.y && triangle.b().y > self.max.y && triangle.c().y > self.max.y {
-            return false;
-        }
-        if triangle.a().z > self.max.z && triangle.b().z > self.max.z && triangle.c().z > self.max.z {
+
+        if swept.min.x > self.max.x || swept.min.y > self.max.y || swept.min.z > self.max.z {
             return false;
         }
 
@@ -3,7 +3,9 @@ use crate::util::ray::Ray;
 
 #[derive(Debug)]
 pub struct BoxIntersection<'i> {
+    #[allow(dead_code)]
     pub ray: &'i Ray,
     pub t: f64,
+    #[allow(dead_code)]
     pub boundingbox: &'i BoundingBox,
 }
@@ -10,8 +10,8 @@ use log::debug;
 //use core::num::dec2flt::rawfp::RawFloat;
 use crate::util::consts::INTERSECTION_EPSILON;
 use core::fmt;
-use serde::export::fmt::Debug;
-use serde::export::Formatter;
+use std::fmt::Debug;
+use std::fmt::Formatter;
 
 mod boundingbox;
 mod boxintersection;
@@ -29,25 +29,26 @@ impl<'d> Debug for KDTreeDataStructure<'d> {
 
 #[allow(clippy::many_single_char_names)]
 fn intersects_triangle<'a>(ray: &'a Ray, triangle: &'a Triangle) -> Option<Intersection<'a>> {
-    let edge1 = triangle.b() - triangle.a();
-    let edge2 = triangle.c() - triangle.a();
+    let [a, b, c] = triangle.vertices_at(ray.time);
+    let edge1 = b - a;
+    let edge2 = c - a;
 
     let h = ray.direction.cross(edge2);
-    let a = edge1.dot(h);
+    let det = edge1.dot(h);
 
-    if -INTERSECTION_EPSILON < a && a < INTERSECTION_EPSILON {
+    if -INTERSECTION_EPSILON < det && det < INTERSECTION_EPSILON {
         return None;
     }
 
-    let f = 1f64 / a;
+    let f = 1f64 / det;
 
-    let s = ray.origin - triangle.a();
+    let s = ray.origin - a;
     let u = f * s.dot(h);
 
     let q = s.cross(edge1);
     let v = f * ray.direction.dot(q);
 
-    if u < 0f64 || u > 1f64 {
+    if !(0f64..=1f64).contains(&u) {
         return None;
     }
 
@@ -75,7 +76,7 @@ impl<'d> KDTreeDataStructure<'d> {
         debug!("Cached triangles locally");
 
         let root = BVHNode::new(triangles);
-        println!("{}", root);
+        debug!("{}", root);
 
         Self { root }
     }
@@ -90,7 +91,7 @@ impl<'d> KDTreeDataStructure<'d> {
                     let mut min = None;
 
                     for triangle in triangles {
-                        if let Some(intersection) = intersects_triangle(ray, &triangle) {
+                        if let Some(intersection) = intersects_triangle(ray, triangle) {
                             min = match min {
                                 None => Some(intersection),
                                 Some(i) if intersection.t < i.t => Some(intersection),
@@ -108,8 +109,8 @@ impl<'d> KDTreeDataStructure<'d> {
                 right,
                 ..
             } => {
-                let dist_l = intersects_bhv(&left, ray);
-                let dist_r = intersects_bhv(&right, ray);
+                let dist_l = intersects_bhv(left, ray);
+                let dist_r = intersects_bhv(right, ray);
 
                 match (dist_l, dist_r) {
                     (None, None) => None,
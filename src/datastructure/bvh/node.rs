@@ -140,7 +140,10 @@ impl<'d> BVHNode<'d> {
         let smallest = smallest.unwrap();
         let current_cost = bounding_box.cost(triangles.len());
 
-        debug!("Smallest possible split cost: {}", smallest.totalcost);
+        debug!(
+            "Smallest possible split cost at depth {}: {}",
+            depth, smallest.totalcost
+        );
         debug!("Parent split cost: {}", current_cost);
 
         if smallest.totalcost >= current_cost {
@@ -1,7 +1,6 @@
 use crate::scene::triangle::Triangle;
 use crate::util::ray::Ray;
 use crate::util::vector::Vector;
-use std::f64::EPSILON;
 
 #[derive(Debug)]
 /// Represents the intersection point between a ray and a triangle.
@@ -20,6 +19,11 @@ impl<'i> Intersection<'i> {
     /// Returns a point in 3d space where the hit occured.
     /// TODO: 3d point in worldspace right?
     pub fn hit_pos(&self) -> Vector {
-        self.ray.origin + self.ray.direction * (self.t - EPSILON)
+        self.ray.origin + self.ray.direction * (self.t - f64::EPSILON)
+    }
+
+    /// The normal of the triangle that was hit, at the time of the ray.
+    pub fn normal(&self) -> Vector {
+        self.triangle.normal_at(self.ray.time)
     }
 }
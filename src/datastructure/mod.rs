@@ -1,6 +1,6 @@
 use crate::datastructure::intersection::Intersection;
 use crate::util::ray::Ray;
-use serde::export::fmt::Debug;
+use std::fmt::Debug;
 
 pub mod basic;
 pub mod bvh;
@@ -0,0 +1,28 @@
+use crate::filter::Filter;
+
+/// Every sample within the radius counts the same. With a radius of half a pixel,
+/// this is a plain average of the samples in the pixel.
+#[derive(Debug)]
+pub struct BoxFilter {
+    radius: f64,
+}
+
+impl BoxFilter {
+    pub fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl Filter for BoxFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        if x.abs() <= self.radius {
+            1.
+        } else {
+            0.
+        }
+    }
+}
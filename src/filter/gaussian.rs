@@ -0,0 +1,30 @@
+use crate::filter::Filter;
+
+/// A Gaussian, shifted down so it reaches zero at the radius instead of being cut off there.
+/// It blurs slightly, but doesn't ring around edges.
+#[derive(Debug)]
+pub struct Gaussian {
+    radius: f64,
+    /// The falloff: the larger, the narrower the Gaussian.
+    alpha: f64,
+}
+
+impl Gaussian {
+    pub fn new(radius: f64, alpha: f64) -> Self {
+        Self { radius, alpha }
+    }
+
+    fn gaussian(&self, x: f64) -> f64 {
+        (-self.alpha * x * x).exp()
+    }
+}
+
+impl Filter for Gaussian {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        (self.gaussian(x) - self.gaussian(self.radius)).max(0.)
+    }
+}
@@ -0,0 +1,40 @@
+use crate::filter::Filter;
+use std::f64;
+
+/// A sinc windowed by a wider sinc (Lanczos), the sharpest of the filters.
+/// It has negative lobes, so high contrast edges can ring a little.
+#[derive(Debug)]
+pub struct Lanczos {
+    radius: f64,
+    /// The number of lobes of the sinc that fit in the window.
+    tau: f64,
+}
+
+impl Lanczos {
+    pub fn new(radius: f64, tau: f64) -> Self {
+        Self { radius, tau }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    let x = x.abs() * f64::consts::PI;
+    if x < 1e-5 {
+        1.
+    } else {
+        x.sin() / x
+    }
+}
+
+impl Filter for Lanczos {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        if x.abs() > self.radius {
+            0.
+        } else {
+            sinc(x) * sinc(x / self.tau)
+        }
+    }
+}
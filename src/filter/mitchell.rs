@@ -0,0 +1,48 @@
+use crate::filter::Filter;
+
+/// The Mitchell-Netravali filter ("Reconstruction Filters in Computer Graphics"), a cubic
+/// with slightly negative lobes that keeps edges sharp. `b` and `c` trade blurring against
+/// ringing, b = c = 1/3 is the balance recommended in the paper.
+#[derive(Debug)]
+pub struct Mitchell {
+    radius: f64,
+    b: f64,
+    c: f64,
+}
+
+impl Mitchell {
+    pub fn new(radius: f64, b: f64, c: f64) -> Self {
+        Self { radius, b, c }
+    }
+
+    /// The cubic on [-2, 2].
+    fn cubic(&self, x: f64) -> f64 {
+        let (b, c) = (self.b, self.c);
+        let x = x.abs();
+
+        let value = if x < 1. {
+            (12. - 9. * b - 6. * c) * x.powi(3)
+                + (-18. + 12. * b + 6. * c) * x.powi(2)
+                + (6. - 2. * b)
+        } else if x < 2. {
+            (-b - 6. * c) * x.powi(3)
+                + (6. * b + 30. * c) * x.powi(2)
+                + (-12. * b - 48. * c) * x
+                + (8. * b + 24. * c)
+        } else {
+            0.
+        };
+
+        value / 6.
+    }
+}
+
+impl Filter for Mitchell {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        self.cubic(2. * x / self.radius)
+    }
+}
@@ -0,0 +1,60 @@
+//! Reconstruction filters decide how much the samples around a pixel count towards its color.
+//! Every sample is added to all pixels whose filter covers it (the pixel center plus or minus
+//! the radius in both directions), weighted by the filter at its distance to their centers,
+//! and every pixel is the weighted average of the samples it got. Filters wider than a pixel
+//! overlap, so a sample contributes to its neighbouring pixels too.
+use std::fmt::Debug;
+
+pub mod boxfilter;
+pub mod gaussian;
+pub mod lanczos;
+pub mod mitchell;
+pub mod tent;
+
+/// A separable filter: the weight of a sample is the product of the weights of its
+/// horizontal and vertical distance to the center of the pixel.
+pub trait Filter: Send + Sync + Debug {
+    /// Samples further than this from the center of the pixel (in pixels, in either direction)
+    /// have no weight.
+    fn radius(&self) -> f64;
+
+    /// The weight of a sample at distance `x` from the center of the pixel, along one axis.
+    /// Can be negative for filters that sharpen.
+    fn evaluate_1d(&self, x: f64) -> f64;
+
+    fn evaluate(&self, x: f64, y: f64) -> f64 {
+        self.evaluate_1d(x) * self.evaluate_1d(y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filter::boxfilter::BoxFilter;
+    use crate::filter::gaussian::Gaussian;
+    use crate::filter::lanczos::Lanczos;
+    use crate::filter::mitchell::Mitchell;
+    use crate::filter::tent::Tent;
+    use crate::filter::Filter;
+
+    #[test]
+    fn test_filters() {
+        let filters: [&dyn Filter; 5] = [
+            &BoxFilter::new(0.5),
+            &Tent::new(1.),
+            &Gaussian::new(1.5, 2.),
+            &Mitchell::new(2., 1. / 3., 1. / 3.),
+            &Lanczos::new(3., 3.),
+        ];
+
+        for filter in &filters {
+            assert!(filter.evaluate(0., 0.) > 0.);
+            assert_eq!(filter.evaluate(filter.radius() * 1.01, 0.), 0.);
+
+            for x in &[0.1, 0.3, 0.7] {
+                assert!((filter.evaluate_1d(*x) - filter.evaluate_1d(-x)).abs() < 1e-12);
+                // Weights fall off from the center.
+                assert!(filter.evaluate_1d(*x) <= filter.evaluate_1d(0.));
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::filter::Filter;
+
+/// Weights fall off linearly from the center of the pixel to zero at the radius.
+#[derive(Debug)]
+pub struct Tent {
+    radius: f64,
+}
+
+impl Tent {
+    pub fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl Filter for Tent {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        (self.radius - x.abs()).max(0.)
+    }
+}
@@ -1,6 +1,5 @@
 use crate::generator::{Callback, Generator};
 use crate::util::camera::Camera;
-use crate::util::outputbuffer::OutputBuffer;
 use crossbeam::thread;
 use log::info;
 
@@ -16,26 +15,22 @@ impl CrossbeamGenerator {
 }
 
 impl Generator for CrossbeamGenerator {
-    fn generate(&self, camera: &Camera, callback: &Callback) -> OutputBuffer {
-        let mut output = OutputBuffer::with_size(camera.width, camera.height);
-
+    fn generate(&self, camera: &Camera, callback: &Callback) {
         thread::scope(|s| {
             let rows_per_thread = (camera.height / self.threads)
-                + if camera.height % self.threads == 0 {
+                + if camera.height.is_multiple_of(self.threads) {
                     0
                 } else {
                     1
                 };
 
-            for (index, chunk) in output.chunks_mut(rows_per_thread).enumerate() {
-                let start_y = index * rows_per_thread;
+            for start_y in (0..camera.height).step_by(rows_per_thread.max(1)) {
+                let end_y = (start_y + rows_per_thread).min(camera.height);
 
                 s.spawn(move |_| {
-                    for y in start_y..(start_y + chunk.len()) {
-                        let row = &mut chunk[y - start_y];
-
-                        for (x, item) in row.iter_mut().enumerate().take(camera.width)  {
-                            *item = callback(x, y);
+                    for y in start_y..end_y {
+                        for x in 0..camera.width {
+                            callback(x, y);
                         }
                         info!("Finished row {}", y);
                     }
@@ -43,7 +38,5 @@ impl Generator for CrossbeamGenerator {
             }
         })
         .expect("One of the threads in the threadpool has panicked!");
-
-        output
     }
 }
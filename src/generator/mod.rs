@@ -2,34 +2,47 @@ use crate::datastructure::DataStructure;
 use crate::raytracer::RayTracer;
 use crate::shader::Shader;
 use crate::util::camera::Camera;
+use crate::util::film::Film;
 use crate::util::outputbuffer::OutputBuffer;
-use crate::util::vector::Vector;
-use serde::export::fmt::Debug;
+use crate::util::rng::{mix_seed, reseed};
+use crate::util::statistics::flush_counts;
+use std::fmt::Debug;
 
 pub mod basic;
 pub mod crossbeam;
 pub mod rayon;
 
-type Callback<'a> = (dyn Fn(usize, usize) -> Vector + Sync + 'a);
+type Callback<'a> = dyn Fn(usize, usize) + Sync + 'a;
 
 /// A generator is a struct that simply iterates over all x-y coordinates in the output image,
-/// and calls generate(x, y) on it. After all pixels are iterated it collects all data
-/// into an `Outputbuffer`.
+/// and calls the callback on it. The raytracer adds the samples of every pixel to a `Film`,
+/// which is turned into an `OutputBuffer` after all pixels are iterated.
 ///
 /// This is important to be it's own subsystem because this iteration can be done in many ways
 /// such as multithreaded, singlethreaded, or even spread over multiple machines.
 pub trait Generator: Debug {
+    /// With a `seed`, the random numbers of every pixel are seeded from the seed and the
+    /// position of the pixel, so the output doesn't depend on which thread renders which pixel.
     fn generate_internal<'g>(
         &self,
         raytracer: &'g dyn RayTracer,
         datastructure: &'g dyn DataStructure,
         shader: &'g dyn Shader,
         camera: &Camera,
+        seed: Option<u64>,
     ) -> OutputBuffer {
+        let film = Film::new(camera.width, camera.height);
+
         self.generate(camera, &|x, y| {
-            raytracer.raytrace(x, y, datastructure, shader, camera)
-        })
+            if let Some(seed) = seed {
+                reseed(mix_seed(seed, (y * camera.width + x) as u64));
+            }
+            raytracer.raytrace(x, y, datastructure, shader, camera, &film);
+            flush_counts();
+        });
+
+        film.to_output()
     }
 
-    fn generate(&self, camera: &Camera, callback: &Callback) -> OutputBuffer;
+    fn generate(&self, camera: &Camera, callback: &Callback);
 }
@@ -1,41 +1,37 @@
 use crate::generator::{Callback, Generator};
 use crate::util::camera::Camera;
-use crate::util::outputbuffer::OutputBuffer;
 use log::info;
-use rayon::iter::IndexedParallelIterator;
-use rayon::iter::IntoParallelRefMutIterator;
+use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
-use rayon::ThreadPoolBuilder;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 
 #[derive(Debug)]
 pub struct RayonGenerator {
-    threads: usize,
+    // A local pool instead of the global one, since the global pool can only be built once.
+    pool: ThreadPool,
 }
 
 impl RayonGenerator {
     pub fn new(threads: usize) -> Self {
-        Self { threads }
+        Self {
+            pool: ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("Couldn't create the rayon threadpool"),
+        }
     }
 }
 
 impl Generator for RayonGenerator {
-    fn generate<'g>(&self, camera: &Camera, callback: &Callback) -> OutputBuffer {
-        // Todo: error handling
-        ThreadPoolBuilder::new()
-            .num_threads(self.threads)
-            .build_global()
-            .unwrap();
+    fn generate<'g>(&self, camera: &Camera, callback: &Callback) {
+        self.pool.install(|| {
+            (0..camera.height).into_par_iter().for_each(|y| {
+                for x in 0..camera.width {
+                    callback(x, y);
+                }
 
-        let mut output = OutputBuffer::with_size(camera.width, camera.height);
-
-        output.par_iter_mut().enumerate().for_each(|(y, row)| {
-            for (x, item) in row.iter_mut().enumerate().take(camera.width) {
-                *item = callback(x, y);
-            }
-
-            info!("Finished row {}", y);
+                info!("Finished row {}", y);
+            })
         });
-
-        output
     }
 }
@@ -1,28 +1,36 @@
+use std::error::Error;
+use std::{env, process};
 
-use crate::config::Config;
-use log::LevelFilter;
-use simple_logging;
-
+mod cli;
+mod compare;
 mod config;
 mod datastructure;
+mod filter;
 mod postprocessors;
 mod raytracer;
 mod renderer;
+mod sampler;
 mod scene;
 mod generator;
+mod output;
 mod shader;
 mod util;
 
 fn main() {
-    simple_logging::log_to_stderr(LevelFilter::Debug);
+    let result = cli::parse(env::args().skip(1)).and_then(|command| command.run());
 
-    // Config::default().dump("config.yml")
-    //     .unwrap();
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
 
-    Config::load("configurations/monte-carlo.yml")
-        .unwrap()
-        .run()
-        .unwrap();
+        let mut source = e.source();
+        while let Some(cause) = source {
+            eprintln!("  caused by: {}", cause);
+            source = cause.source();
+        }
 
-    // GlowStoneGamma.run()
+        if let cli::error::CliError::Usage(_) = e {
+            eprintln!("\n{}", cli::USAGE);
+        }
+        process::exit(e.exit_code());
+    }
 }
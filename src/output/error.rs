@@ -0,0 +1,60 @@
+use image::ImageError;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum OutputError {
+    IoError {
+        path: PathBuf,
+        error: io::Error,
+    },
+    ImageError {
+        path: PathBuf,
+        // Boxed because image errors are large
+        error: Box<ImageError>,
+    },
+    UnknownFormat(PathBuf),
+}
+
+impl Display for OutputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputError::IoError { path, .. } | OutputError::ImageError { path, .. } => {
+                write!(f, "couldn't write image '{}'", path.display())
+            }
+            OutputError::UnknownFormat(path) => write!(
+                f,
+                "can't tell the image format of '{}' from its extension",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl Error for OutputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            OutputError::IoError { error, .. } => Some(error),
+            OutputError::ImageError { error, .. } => Some(error.as_ref()),
+            OutputError::UnknownFormat(_) => None,
+        }
+    }
+}
+
+impl OutputError {
+    pub fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |error| OutputError::IoError { path, error }
+    }
+
+    pub fn image(path: impl Into<PathBuf>) -> impl FnOnce(ImageError) -> Self {
+        let path = path.into();
+        move |error| OutputError::ImageError {
+            path,
+            error: Box::new(error),
+        }
+    }
+}
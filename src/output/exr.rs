@@ -0,0 +1,98 @@
+use crate::util::outputbuffer::OutputBuffer;
+use crate::util::vector::Vector;
+use image::ImageResult;
+use std::io::Write;
+
+/// Identifies a file as OpenEXR.
+const MAGIC: i32 = 20000630;
+/// Version 2, single part scanline file without long attribute names.
+const VERSION: i32 = 2;
+/// The channels of a pixel. OpenEXR requires them in alphabetical order.
+const CHANNELS: [&str; 3] = ["B", "G", "R"];
+/// Pixel type of 32 bit floats.
+const PIXEL_TYPE_FLOAT: i32 = 2;
+
+/// Writes an uncompressed scanline OpenEXR image (https://www.openexr.com/documentation.html)
+/// with 32 bit float R, G and B channels.
+pub fn write(buffer: &OutputBuffer, mut out: impl Write) -> ImageResult<()> {
+    let height = buffer.len();
+    let width = buffer.first().map_or(0, Vec::len);
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&MAGIC.to_le_bytes());
+    header.extend_from_slice(&VERSION.to_le_bytes());
+
+    let mut channels = Vec::new();
+    for name in &CHANNELS {
+        channels.extend_from_slice(name.as_bytes());
+        channels.push(0);
+        channels.extend_from_slice(&PIXEL_TYPE_FLOAT.to_le_bytes());
+        // pLinear and three reserved bytes
+        channels.extend_from_slice(&[0; 4]);
+        // x and y sampling
+        channels.extend_from_slice(&1i32.to_le_bytes());
+        channels.extend_from_slice(&1i32.to_le_bytes());
+    }
+    channels.push(0);
+
+    let mut window = Vec::new();
+    for i in &[0, 0, width as i32 - 1, height as i32 - 1] {
+        window.extend_from_slice(&i32::to_le_bytes(*i));
+    }
+
+    attribute(&mut header, "channels", "chlist", &channels);
+    // No compression
+    attribute(&mut header, "compression", "compression", &[0]);
+    attribute(&mut header, "dataWindow", "box2i", &window);
+    attribute(&mut header, "displayWindow", "box2i", &window);
+    // Increasing y, the top row comes first
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    attribute(
+        &mut header,
+        "pixelAspectRatio",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(
+        &mut header,
+        "screenWindowWidth",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    header.push(0);
+
+    // Every uncompressed chunk holds a single row: its y coordinate, its size and the data.
+    let row_size = width * CHANNELS.len() * 4;
+    let chunk_size = 8 + row_size;
+    let first_chunk = header.len() + height * 8;
+    for y in 0..height {
+        header.extend_from_slice(&((first_chunk + y * chunk_size) as u64).to_le_bytes());
+    }
+    out.write_all(&header)?;
+
+    for (y, row) in buffer.iter().enumerate() {
+        out.write_all(&(y as i32).to_le_bytes())?;
+        out.write_all(&(row_size as i32).to_le_bytes())?;
+
+        // Every row stores all the values of one channel, then the next channel.
+        let channels: [fn(&Vector) -> f64; 3] = [|p| p.z, |p| p.y, |p| p.x];
+        for channel in &channels {
+            for pixel in row {
+                out.write_all(&(channel(pixel) as f32).to_le_bytes())?;
+            }
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
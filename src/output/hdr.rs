@@ -0,0 +1,20 @@
+use crate::util::outputbuffer::OutputBuffer;
+use image::codecs::hdr::HdrEncoder;
+use image::{ImageResult, Rgb};
+use std::io::Write;
+
+/// Writes a Radiance RGBE image. It stores a shared exponent per pixel, so it covers a
+/// large range of values at a quarter of the size of a float image.
+pub fn write(buffer: &OutputBuffer, out: impl Write) -> ImageResult<()> {
+    let height = buffer.len();
+    let width = buffer.first().map_or(0, Vec::len);
+
+    let pixels: Vec<Rgb<f32>> = buffer
+        .iter()
+        .flatten()
+        // RGBE can't store negative values
+        .map(|pixel| Rgb([pixel.x, pixel.y, pixel.z].map(|i| i.max(0.) as f32)))
+        .collect();
+
+    HdrEncoder::new(out).encode(&pixels, width, height)
+}
@@ -0,0 +1,281 @@
+use crate::output::error::OutputError;
+use crate::util::outputbuffer::OutputBuffer;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, DynamicImage, ImageOutputFormat, ImageResult};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+pub mod error;
+mod exr;
+mod hdr;
+mod pfm;
+
+/// Settings for the formats that have them.
+#[derive(Debug)]
+pub struct OutputOptions {
+    /// Quality of jpeg images, from 1 to 100.
+    pub jpeg_quality: u8,
+    /// Write png images with 16 bits per channel instead of 8.
+    pub png_16bit: bool,
+}
+
+/// The file formats a render can be saved as.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    /// 8 bit per channel bitmap. Colors are clamped to [0, 1], like all other
+    /// integer formats.
+    Bmp,
+    /// 8 or 16 bit per channel png.
+    Png,
+    /// 8 bit per channel jpeg, with a configurable quality.
+    Jpeg,
+    /// 8 bit per channel targa.
+    Tga,
+    /// Portable Float Map, linear 32 bit floats.
+    Pfm,
+    /// Radiance RGBE, linear floats with a shared exponent per pixel.
+    Hdr,
+    /// OpenEXR, linear 32 bit floats.
+    Exr,
+}
+
+impl ImageFormat {
+    /// Picks the format from the extension of `path` (case insensitive).
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, OutputError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|i| i.to_str())
+            .map(str::to_lowercase);
+
+        match extension.as_deref() {
+            Some("bmp") => Ok(ImageFormat::Bmp),
+            Some("png") => Ok(ImageFormat::Png),
+            Some("jpg") | Some("jpeg") => Ok(ImageFormat::Jpeg),
+            Some("tga") => Ok(ImageFormat::Tga),
+            Some("pfm") => Ok(ImageFormat::Pfm),
+            Some("hdr") => Ok(ImageFormat::Hdr),
+            Some("exr") => Ok(ImageFormat::Exr),
+            _ => Err(OutputError::UnknownFormat(path.to_path_buf())),
+        }
+    }
+
+    /// Whether the format stores the linear float colors instead of clamping them.
+    pub fn is_float(self) -> bool {
+        matches!(self, ImageFormat::Pfm | ImageFormat::Hdr | ImageFormat::Exr)
+    }
+
+    pub fn write(
+        self,
+        buffer: &OutputBuffer,
+        path: impl AsRef<Path>,
+        options: &OutputOptions,
+    ) -> Result<(), OutputError> {
+        let path = path.as_ref();
+        let out = BufWriter::new(File::create(path).map_err(OutputError::io(path))?);
+
+        match self {
+            ImageFormat::Bmp => write_rgb8(buffer, ImageOutputFormat::Bmp, out),
+            ImageFormat::Png if options.png_16bit => write_png16(buffer, out),
+            ImageFormat::Png => write_rgb8(buffer, ImageOutputFormat::Png, out),
+            ImageFormat::Jpeg => {
+                write_rgb8(buffer, ImageOutputFormat::Jpeg(options.jpeg_quality), out)
+            }
+            ImageFormat::Tga => write_rgb8(buffer, ImageOutputFormat::Tga, out),
+            ImageFormat::Pfm => pfm::write(buffer, out),
+            ImageFormat::Hdr => hdr::write(buffer, out),
+            ImageFormat::Exr => exr::write(buffer, out),
+        }
+        .map_err(OutputError::image(path))
+    }
+}
+
+/// `DynamicImage::write_to` passes 16 bit images to the png encoder in native byte order,
+/// but png is big endian. So the bytes are ordered here.
+fn write_png16(buffer: &OutputBuffer, mut out: impl Write) -> ImageResult<()> {
+    let image = buffer.to_rgb16();
+    let bytes: Vec<u8> = image.iter().flat_map(|i| i.to_be_bytes()).collect();
+
+    PngEncoder::new(&mut out).encode(&bytes, image.width(), image.height(), ColorType::Rgb16)?;
+    out.flush()?;
+    Ok(())
+}
+
+fn write_rgb8(
+    buffer: &OutputBuffer,
+    format: ImageOutputFormat,
+    mut out: impl Write,
+) -> ImageResult<()> {
+    DynamicImage::ImageRgb8(buffer.to_rgb8()).write_to(&mut out, format)?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::output::ImageFormat;
+    use crate::util::outputbuffer::OutputBuffer;
+    use crate::util::vector::Vector;
+    use image::codecs::hdr::HdrDecoder;
+    use std::collections::HashMap;
+    use std::convert::TryInto;
+
+    fn test_buffer() -> OutputBuffer {
+        let mut buffer = OutputBuffer::with_size(3, 2);
+        buffer.set_at(0, 0, Vector::new(1., 0.5, 0.25));
+        buffer.set_at(2, 0, Vector::new(2.5, 0., 100.));
+        buffer.set_at(1, 1, Vector::new(0., 64., 8.));
+        buffer
+    }
+
+    fn read_i32(data: &[u8], at: usize) -> i32 {
+        i32::from_le_bytes(data[at..at + 4].try_into().unwrap())
+    }
+
+    fn read_f32(data: &[u8], at: usize) -> f32 {
+        f32::from_le_bytes(data[at..at + 4].try_into().unwrap())
+    }
+
+    /// Reads the null terminated string at `*at` and moves `*at` past it.
+    fn read_str<'a>(data: &'a [u8], at: &mut usize) -> &'a str {
+        let end = *at + data[*at..].iter().position(|&b| b == 0).unwrap();
+        let s = std::str::from_utf8(&data[*at..end]).unwrap();
+        *at = end + 1;
+        s
+    }
+
+    /// Reads an uncompressed scanline OpenEXR image with float channels, as far
+    /// as `exr::write` needs it.
+    fn read_exr(data: &[u8]) -> OutputBuffer {
+        assert_eq!(read_i32(data, 0), 20000630);
+        assert_eq!(read_i32(data, 4), 2);
+
+        let mut attributes = HashMap::new();
+        let mut at = 8;
+        loop {
+            let name = read_str(data, &mut at);
+            if name.is_empty() {
+                break;
+            }
+            let kind = read_str(data, &mut at);
+            let size = read_i32(data, at) as usize;
+            attributes.insert(name, (kind, &data[at + 4..at + 4 + size]));
+            at += 4 + size;
+        }
+
+        assert_eq!(attributes["compression"], ("compression", &[0][..]));
+        let (kind, window) = attributes["dataWindow"];
+        assert_eq!(kind, "box2i");
+        assert_eq!((read_i32(window, 0), read_i32(window, 4)), (0, 0));
+        let width = read_i32(window, 8) as usize + 1;
+        let height = read_i32(window, 12) as usize + 1;
+
+        let (kind, channel_list) = attributes["channels"];
+        assert_eq!(kind, "chlist");
+        let mut channels = Vec::new();
+        let mut channel_at = 0;
+        loop {
+            let name = read_str(channel_list, &mut channel_at);
+            if name.is_empty() {
+                break;
+            }
+            assert_eq!(read_i32(channel_list, channel_at), 2);
+            channels.push(name);
+            channel_at += 16;
+        }
+        assert_eq!(channel_at, channel_list.len());
+
+        let mut buffer = OutputBuffer::with_size(width, height);
+        for row in 0..height {
+            let offset = u64::from_le_bytes(data[at + row * 8..][..8].try_into().unwrap());
+            let chunk = &data[offset as usize..];
+            let y = read_i32(chunk, 0) as usize;
+            assert_eq!(read_i32(chunk, 4) as usize, width * channels.len() * 4);
+
+            for x in 0..width {
+                let mut pixel = Vector::repeated(0.);
+                for (index, channel) in channels.iter().enumerate() {
+                    let value = read_f32(chunk, 8 + (index * width + x) * 4) as f64;
+                    match *channel {
+                        "R" => pixel.x = value,
+                        "G" => pixel.y = value,
+                        "B" => pixel.z = value,
+                        _ => panic!("unexpected channel {}", channel),
+                    }
+                }
+                buffer.set_at(x, y, pixel);
+            }
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            ImageFormat::from_path("render.bmp").unwrap(),
+            ImageFormat::Bmp
+        );
+        assert_eq!(
+            ImageFormat::from_path("a/render.EXR").unwrap(),
+            ImageFormat::Exr
+        );
+        assert_eq!(
+            ImageFormat::from_path("render.jpeg").unwrap(),
+            ImageFormat::Jpeg
+        );
+        assert!(ImageFormat::from_path("render").is_err());
+        assert!(ImageFormat::Exr.is_float());
+        assert!(!ImageFormat::Png.is_float());
+        assert!(ImageFormat::from_path("render.xyz").is_err());
+    }
+
+    #[test]
+    fn test_pfm() {
+        let mut buffer = OutputBuffer::with_size(2, 1);
+        buffer.set_at(1, 0, Vector::new(2.5, 0., 100.));
+
+        let mut out = Vec::new();
+        super::pfm::write(&buffer, &mut out).unwrap();
+
+        let header = b"PF\n2 1\n-1.0\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(out.len(), header.len() + 2 * 3 * 4);
+        assert_eq!(&out[header.len() + 12..][..4], &2.5f32.to_le_bytes());
+        assert_eq!(&out[header.len() + 20..], &100f32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_exr_round_trip() {
+        let buffer = test_buffer();
+
+        let mut out = Vec::new();
+        super::exr::write(&buffer, &mut out).unwrap();
+
+        assert_eq!(*read_exr(&out), *buffer);
+    }
+
+    #[test]
+    fn test_hdr_round_trip() {
+        let mut buffer = test_buffer();
+        // RGBE can't store negative values, they are written as 0.
+        buffer.set_at(1, 0, Vector::new(-1., 3., 0.));
+
+        let mut out = Vec::new();
+        super::hdr::write(&buffer, &mut out).unwrap();
+
+        let decoder = HdrDecoder::new(out.as_slice()).unwrap();
+        let metadata = decoder.metadata();
+        assert_eq!((metadata.width, metadata.height), (3, 2));
+        let pixels = decoder.read_image_hdr().unwrap();
+
+        buffer.set_at(1, 0, Vector::new(0., 3., 0.));
+        for (pixel, expected) in pixels.iter().zip(buffer.iter().flatten()) {
+            // RGBE shares an exponent between the channels, so small channels lose precision.
+            let largest = expected.x.max(expected.y).max(expected.z);
+            for (value, expected) in pixel.0.iter().zip(&[expected.x, expected.y, expected.z]) {
+                assert!((*value as f64 - expected).abs() <= largest / 128.);
+            }
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::util::outputbuffer::OutputBuffer;
+use image::ImageResult;
+use std::io::Write;
+
+/// Writes a color Portable Float Map (http://www.pauldebevec.com/Research/HDR/PFM/).
+/// Pixels are stored as little endian floats, from the bottom row to the top row.
+pub fn write(buffer: &OutputBuffer, mut out: impl Write) -> ImageResult<()> {
+    let height = buffer.len();
+    let width = buffer.first().map_or(0, Vec::len);
+
+    // A negative scale means the data is little endian.
+    write!(out, "PF\n{} {}\n-1.0\n", width, height)?;
+
+    for row in buffer.iter().rev() {
+        for pixel in row {
+            for channel in &[pixel.x, pixel.y, pixel.z] {
+                out.write_all(&(*channel as f32).to_le_bytes())?;
+            }
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
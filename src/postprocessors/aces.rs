@@ -0,0 +1,34 @@
+use crate::postprocessors::PostProcessor;
+use crate::util::outputbuffer::OutputBuffer;
+use crate::util::vector::Vector;
+
+/// Filmic tone mapping with Krzysztof Narkowicz's fit of the ACES reference curve
+/// (https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/).
+/// Gives more contrast than Reinhard and saturated highlights fade to white.
+#[derive(Debug)]
+pub struct Aces;
+
+impl Aces {
+    fn map_channel(x: f64) -> f64 {
+        const A: f64 = 2.51;
+        const B: f64 = 0.03;
+        const C: f64 = 2.43;
+        const D: f64 = 0.59;
+        const E: f64 = 0.14;
+
+        let x = x.max(0.);
+        ((x * (A * x + B)) / (x * (C * x + D) + E)).clamp(0., 1.)
+    }
+}
+
+impl PostProcessor for Aces {
+    fn process(&self, buffer: OutputBuffer) -> OutputBuffer {
+        buffer.map_pixels(|pixel| {
+            Vector::new(
+                Self::map_channel(pixel.x),
+                Self::map_channel(pixel.y),
+                Self::map_channel(pixel.z),
+            )
+        })
+    }
+}
@@ -0,0 +1,23 @@
+use crate::postprocessors::PostProcessor;
+use crate::util::outputbuffer::OutputBuffer;
+
+/// Makes the image brighter or darker by a number of stops.
+/// Every stop doubles the brightness, negative stops make the image darker.
+#[derive(Debug)]
+pub struct Exposure {
+    multiplier: f64,
+}
+
+impl Exposure {
+    pub fn new(stops: f64) -> Self {
+        Self {
+            multiplier: 2f64.powf(stops),
+        }
+    }
+}
+
+impl PostProcessor for Exposure {
+    fn process(&self, buffer: OutputBuffer) -> OutputBuffer {
+        buffer.map_pixels(|pixel| pixel * self.multiplier)
+    }
+}
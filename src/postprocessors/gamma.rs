@@ -1,26 +1,22 @@
 use crate::postprocessors::PostProcessor;
 use crate::util::outputbuffer::OutputBuffer;
+use crate::util::vector::Vector;
 
-/// This postprocessor returns the same image as it got in.
-/// Useful if you don't want to postprocess at all.
+/// Raises every color channel to the power `exponent`.
+/// Exponents below 1 brighten the dark parts of the image.
 #[derive(Debug)]
-pub struct Gamma;
+pub struct Gamma {
+    exponent: f64,
+}
+
+impl Gamma {
+    pub fn new(exponent: f64) -> Self {
+        Self { exponent }
+    }
+}
 
 impl PostProcessor for Gamma {
     fn process(&self, buffer: OutputBuffer) -> OutputBuffer {
-        let height = buffer.len();
-        let width = if height > 0 { buffer[0].len() } else { 0 };
-
-        let mut new_buffer = OutputBuffer::new();
-
-        for y in 0..height {
-            let mut row = Vec::new();
-            for x in 0..width {
-                row.push(buffer[y][x].gamma(0.5f64));
-            }
-            new_buffer.push(row);
-        }
-
-        new_buffer
+        buffer.map_pixels(|pixel| pixel.max(&Vector::repeated(0.)).powf(self.exponent))
     }
 }
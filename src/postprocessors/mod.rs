@@ -1,9 +1,13 @@
 use crate::util::outputbuffer::OutputBuffer;
-use serde::export::fmt::Debug;
+use std::fmt::Debug;
 
+pub mod aces;
+pub mod exposure;
 pub mod gamma;
 pub mod group;
 pub mod identity;
+pub mod reinhard;
+pub mod srgb;
 
 /// After raytracing, a `PostProcessor` will be applied to the outputbuffer.
 /// There are many options. If multiple postprocessor steps are required,
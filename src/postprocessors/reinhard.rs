@@ -0,0 +1,57 @@
+use crate::postprocessors::PostProcessor;
+use crate::util::outputbuffer::OutputBuffer;
+
+/// Reinhard tone mapping (http://www.cmap.polytechnique.fr/~peyre/cours/x2005signal/hdr_photographic.pdf).
+/// Compresses the luminance of every pixel into [0, 1) while keeping its hue.
+#[derive(Debug)]
+pub struct Reinhard {
+    /// The luminance that is mapped to white. Without it,
+    /// only infinitely bright pixels become white.
+    white_point: Option<f64>,
+}
+
+impl Reinhard {
+    pub fn simple() -> Self {
+        Self { white_point: None }
+    }
+
+    pub fn extended(white_point: f64) -> Self {
+        Self {
+            white_point: Some(white_point),
+        }
+    }
+
+    fn map_luminance(&self, luminance: f64) -> f64 {
+        match self.white_point {
+            None => luminance / (1. + luminance),
+            Some(white) => luminance * (1. + luminance / (white * white)) / (1. + luminance),
+        }
+    }
+}
+
+impl PostProcessor for Reinhard {
+    fn process(&self, buffer: OutputBuffer) -> OutputBuffer {
+        buffer.map_pixels(|pixel| {
+            let luminance = pixel.luminance();
+            if luminance <= 0. {
+                return pixel;
+            }
+
+            pixel * (self.map_luminance(luminance) / luminance)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::postprocessors::reinhard::Reinhard;
+
+    #[test]
+    fn test_map_luminance() {
+        assert_eq!(Reinhard::simple().map_luminance(1.), 0.5);
+        assert_eq!(Reinhard::simple().map_luminance(0.), 0.);
+
+        assert_eq!(Reinhard::extended(4.).map_luminance(4.), 1.);
+        assert!(Reinhard::extended(4.).map_luminance(1.) > 0.5);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::postprocessors::PostProcessor;
+use crate::util::outputbuffer::OutputBuffer;
+use crate::util::vector::Vector;
+
+/// Encodes linear colors with the sRGB transfer function, which is what image viewers
+/// expect from 8 bit images. This should be the last step, after tone mapping.
+#[derive(Debug)]
+pub struct Srgb;
+
+impl Srgb {
+    fn encode(linear: f64) -> f64 {
+        let linear = linear.clamp(0., 1.);
+        if linear <= 0.0031308 {
+            12.92 * linear
+        } else {
+            1.055 * linear.powf(1. / 2.4) - 0.055
+        }
+    }
+}
+
+impl PostProcessor for Srgb {
+    fn process(&self, buffer: OutputBuffer) -> OutputBuffer {
+        buffer.map_pixels(|pixel| {
+            Vector::new(
+                Self::encode(pixel.x),
+                Self::encode(pixel.y),
+                Self::encode(pixel.z),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::postprocessors::srgb::Srgb;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(Srgb::encode(0.), 0.);
+        assert!((Srgb::encode(1.) - 1.).abs() < 1e-12);
+        assert!((Srgb::encode(0.18) - 0.4614).abs() < 1e-4);
+        assert!((Srgb::encode(0.001) - 0.01292).abs() < 1e-12);
+        assert!((Srgb::encode(5.) - 1.).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::datastructure::DataStructure;
+use crate::filter::Filter;
+use crate::raytracer::{trace_jittered_sample, RayTracer};
+use crate::sampler::{SampleStream, Sampler};
+use crate::shader::Shader;
+use crate::util::camera::Camera;
+use crate::util::film::Film;
+use crate::util::rng::get_rng;
+use rand::Rng;
+
+/// Pixels darker than this are judged on the error relative to this luminance instead of
+/// their own, so almost black pixels don't need many samples for tiny absolute errors.
+const MIN_LUMINANCE: f64 = 0.01;
+
+/// A jittered multisampling raytracer that samples a pixel until the estimated error of its
+/// luminance is small enough. Flat regions stop after a few samples, while noisy ones
+/// (soft shadows, caustics) get up to `max_samples`.
+#[derive(Debug)]
+pub struct AdaptiveTracer {
+    min_samples: usize,
+    max_samples: usize,
+    /// The standard error of the mean luminance of a pixel, relative to that luminance,
+    /// below which the pixel is done.
+    max_error: f64,
+    sampler: Box<dyn Sampler>,
+    filter: Box<dyn Filter>,
+}
+
+impl AdaptiveTracer {
+    pub fn new(
+        min_samples: usize,
+        max_samples: usize,
+        max_error: f64,
+        sampler: Box<dyn Sampler>,
+        filter: Box<dyn Filter>,
+    ) -> Self {
+        Self {
+            min_samples,
+            max_samples,
+            max_error,
+            sampler,
+            filter,
+        }
+    }
+}
+
+impl RayTracer for AdaptiveTracer {
+    fn raytrace<'r>(
+        &self,
+        x: usize,
+        y: usize,
+        datastructure: &'r (dyn DataStructure + 'r),
+        shader: &'r (dyn Shader + 'r),
+        camera: &Camera,
+        film: &Film,
+    ) {
+        let seed = get_rng(|mut r| r.gen::<u64>());
+
+        let mut luminance = RunningVariance::default();
+        for index in 0..self.max_samples {
+            if index >= self.min_samples && luminance.relative_error() <= self.max_error {
+                break;
+            }
+
+            // The pixel may stop at any sample, so samplers whose first samples are spread
+            // evenly (like sobol) work best.
+            let mut samples =
+                SampleStream::new(self.sampler.as_ref(), seed, index, self.max_samples);
+
+            let color = trace_jittered_sample(
+                x,
+                y,
+                datastructure,
+                shader,
+                camera,
+                self.filter.as_ref(),
+                film,
+                &mut samples,
+            );
+            luminance.add(color.luminance());
+        }
+    }
+}
+
+/// The running mean and variance of a series of values (Welford's algorithm).
+#[derive(Debug, Default)]
+struct RunningVariance {
+    count: usize,
+    mean: f64,
+    /// The sum of the squared differences from the mean.
+    squared_deviations: f64,
+}
+
+impl RunningVariance {
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.squared_deviations += delta * (value - self.mean);
+    }
+
+    /// The standard error of the mean relative to the mean. Infinite until there are
+    /// two values, since one value says nothing about the variance.
+    ///
+    /// Values that all agree don't show that there is no variance: two black samples are as
+    /// likely in a penumbra as in a shadow. So the variance is at least what it would be if
+    /// one of the values differed from the others by the mean, which keeps the error at
+    /// 1 / count or more.
+    fn relative_error(&self) -> f64 {
+        if self.count < 2 {
+            return f64::INFINITY;
+        }
+
+        let count = self.count as f64;
+        let scale = self.mean.abs().max(MIN_LUMINANCE);
+        let variance = (self.squared_deviations / (count - 1.)).max(scale * scale / count);
+        (variance / count).sqrt() / scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::adaptive::RunningVariance;
+
+    #[test]
+    fn test_running_variance() {
+        let mut constant = RunningVariance::default();
+        constant.add(0.5);
+        assert!(constant.relative_error().is_infinite());
+        constant.add(0.5);
+        // Values that agree only show a small error after many of them.
+        assert!((constant.relative_error() - 0.5).abs() < 1e-12);
+        for _ in 0..48 {
+            constant.add(0.5);
+        }
+        assert!((constant.relative_error() - 0.02).abs() < 1e-12);
+
+        let mut noisy = RunningVariance::default();
+        for value in &[1., 3., 1., 3.] {
+            noisy.add(*value);
+        }
+        assert_eq!(noisy.mean, 2.);
+        // A variance of 4/3 over 4 values gives a standard error of sqrt(1/3) on a mean of 2.
+        assert!((noisy.relative_error() - (1f64 / 3.).sqrt() / 2.).abs() < 1e-12);
+
+        // More of the same values lower the error.
+        let error = noisy.relative_error();
+        for value in &[1., 3., 1., 3.] {
+            noisy.add(*value);
+        }
+        assert!(noisy.relative_error() < error);
+    }
+}
@@ -1,8 +1,9 @@
 use crate::datastructure::DataStructure;
-use crate::raytracer::RayTracer;
+use crate::raytracer::{trace_sample, RayTracer};
+use crate::sampler::SampleStream;
 use crate::shader::Shader;
 use crate::util::camera::Camera;
-use crate::util::vector::Vector;
+use crate::util::film::Film;
 
 #[derive(Debug)]
 pub struct BasicRaytracer;
@@ -15,8 +16,17 @@ impl RayTracer for BasicRaytracer {
         datastructure: &'r (dyn DataStructure + 'r),
         shader: &'r (dyn Shader + 'r),
         camera: &Camera,
-    ) -> Vector {
-        let ray = camera.generate_ray(x as f64, y as f64);
-        shader.shade(&ray, datastructure)
+        film: &Film,
+    ) {
+        let mut samples = SampleStream::random();
+        let color = trace_sample(
+            x as f64,
+            y as f64,
+            datastructure,
+            shader,
+            camera,
+            &mut samples,
+        );
+        film.add_pixel_sample(x, y, color);
     }
 }
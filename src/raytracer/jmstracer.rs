@@ -1,19 +1,32 @@
 use crate::datastructure::DataStructure;
-use crate::raytracer::RayTracer;
+use crate::filter::Filter;
+use crate::raytracer::{trace_jittered_sample, RayTracer};
+use crate::sampler::{SampleStream, Sampler};
 use crate::shader::Shader;
 use crate::util::camera::Camera;
+use crate::util::film::Film;
 use crate::util::rng::get_rng;
-use crate::util::vector::Vector;
 use rand::Rng;
 
 #[derive(Debug)]
 pub struct JMSTracer {
     samples_per_pixel: usize,
+    sampler: Box<dyn Sampler>,
+    /// Weighs every sample into the pixels around it.
+    filter: Box<dyn Filter>,
 }
 
 impl JMSTracer {
-    pub fn new(samples_per_pixel: usize) -> Self {
-        Self { samples_per_pixel }
+    pub fn new(
+        samples_per_pixel: usize,
+        sampler: Box<dyn Sampler>,
+        filter: Box<dyn Filter>,
+    ) -> Self {
+        Self {
+            samples_per_pixel,
+            sampler,
+            filter,
+        }
     }
 }
 
@@ -25,17 +38,24 @@ impl RayTracer for JMSTracer {
         datastructure: &'r (dyn DataStructure + 'r),
         shader: &'r (dyn Shader + 'r),
         camera: &Camera,
-    ) -> Vector {
-        let mut out = Vector::repeated(0f64);
-        for _ in 0..self.samples_per_pixel {
-            let ray = camera.generate_ray(
-                x as f64 + get_rng(|mut r| r.gen::<f64>()),
-                y as f64 + get_rng(|mut r| r.gen::<f64>()),
-            );
+        film: &Film,
+    ) {
+        let seed = get_rng(|mut r| r.gen::<u64>());
 
-            out += shader.shade(&ray, datastructure);
-        }
+        for index in 0..self.samples_per_pixel {
+            let mut samples =
+                SampleStream::new(self.sampler.as_ref(), seed, index, self.samples_per_pixel);
 
-        out / self.samples_per_pixel as f64
+            trace_jittered_sample(
+                x,
+                y,
+                datastructure,
+                shader,
+                camera,
+                self.filter.as_ref(),
+                film,
+                &mut samples,
+            );
+        }
     }
 }
@@ -1,16 +1,22 @@
 use crate::datastructure::DataStructure;
+use crate::filter::Filter;
+use crate::sampler::SampleStream;
 use crate::shader::Shader;
 use crate::util::camera::Camera;
+use crate::util::film::Film;
+use crate::util::statistics::{count_ray, RayType};
 use crate::util::vector::Vector;
-use serde::export::fmt::Debug;
+use std::fmt::Debug;
 
+pub mod adaptive;
 pub mod basic;
 pub mod jmstracer;
 pub mod mstracer;
 
 /// A raytracer is a struct that takes an x and y coordinate on the screen,
 /// and generates a ray associated with that coordinate. Then this ray can be passed
-/// to a shader to get a color associated with this x-y coordinate.
+/// to a shader to get a color associated with this x-y coordinate. The colors are added
+/// to the film, which weighs them into this pixel and (depending on the filter) its neighbours.
 pub trait RayTracer: Send + Sync + Debug {
     fn raytrace<'r>(
         &self,
@@ -19,5 +25,53 @@ pub trait RayTracer: Send + Sync + Debug {
         datastructure: &'r dyn DataStructure,
         shader: &'r dyn Shader,
         camera: &Camera,
-    ) -> Vector;
+        film: &Film,
+    );
+}
+
+/// The color seen through (x, y) on the image, for one sample. Black where the camera
+/// sees nothing.
+fn trace_sample<'r>(
+    x: f64,
+    y: f64,
+    datastructure: &'r dyn DataStructure,
+    shader: &'r dyn Shader,
+    camera: &Camera,
+    samples: &mut SampleStream,
+) -> Vector {
+    match camera.generate_ray(x, y, samples) {
+        Some(ray) => {
+            count_ray(RayType::Primary);
+            shader.shade(&ray, datastructure, samples)
+        }
+        None => Vector::repeated(0.),
+    }
+}
+
+/// Traces a sample through a random point in pixel (x, y), and adds it to `film` weighted by
+/// `filter`. Returns the color of the sample.
+#[allow(clippy::too_many_arguments)]
+fn trace_jittered_sample<'r>(
+    x: usize,
+    y: usize,
+    datastructure: &'r dyn DataStructure,
+    shader: &'r dyn Shader,
+    camera: &Camera,
+    filter: &dyn Filter,
+    film: &Film,
+    samples: &mut SampleStream,
+) -> Vector {
+    let (u, v) = samples.next_2d();
+
+    let color = trace_sample(
+        x as f64 + u,
+        y as f64 + v,
+        datastructure,
+        shader,
+        camera,
+        samples,
+    );
+    film.add_sample(x, y, (u - 0.5, v - 0.5), color, filter);
+
+    color
 }
@@ -1,17 +1,33 @@
 use crate::datastructure::DataStructure;
-use crate::raytracer::RayTracer;
+use crate::filter::Filter;
+use crate::raytracer::{trace_sample, RayTracer};
+use crate::sampler::{SampleStream, Sampler};
 use crate::shader::Shader;
 use crate::util::camera::Camera;
-use crate::util::vector::Vector;
+use crate::util::film::Film;
+use crate::util::rng::get_rng;
+use rand::Rng;
 
 #[derive(Debug)]
 pub struct MSTracer {
     samples_per_pixel: usize,
+    sampler: Box<dyn Sampler>,
+    /// Weighs the samples, which all go through the top left corner of the pixel, into the
+    /// pixels around it.
+    filter: Box<dyn Filter>,
 }
 
 impl MSTracer {
-    pub fn new(samples_per_pixel: usize) -> Self {
-        Self { samples_per_pixel }
+    pub fn new(
+        samples_per_pixel: usize,
+        sampler: Box<dyn Sampler>,
+        filter: Box<dyn Filter>,
+    ) -> Self {
+        Self {
+            samples_per_pixel,
+            sampler,
+            filter,
+        }
     }
 }
 
@@ -23,13 +39,82 @@ impl RayTracer for MSTracer {
         datastructure: &'r (dyn DataStructure + 'r),
         shader: &'r (dyn Shader + 'r),
         camera: &Camera,
-    ) -> Vector {
-        let mut out = Vector::repeated(0f64);
-        for _ in 0..self.samples_per_pixel {
-            let ray = camera.generate_ray(x as f64, y as f64);
-            out += shader.shade(&ray, datastructure);
+        film: &Film,
+    ) {
+        let seed = get_rng(|mut r| r.gen::<u64>());
+
+        for index in 0..self.samples_per_pixel {
+            let mut samples =
+                SampleStream::new(self.sampler.as_ref(), seed, index, self.samples_per_pixel);
+
+            let color = trace_sample(
+                x as f64,
+                y as f64,
+                datastructure,
+                shader,
+                camera,
+                &mut samples,
+            );
+            film.add_sample(x, y, (-0.5, -0.5), color, self.filter.as_ref());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datastructure::intersection::Intersection;
+    use crate::datastructure::DataStructure;
+    use crate::filter::tent::Tent;
+    use crate::raytracer::mstracer::MSTracer;
+    use crate::raytracer::RayTracer;
+    use crate::sampler::random::RandomSampler;
+    use crate::sampler::SampleStream;
+    use crate::shader::Shader;
+    use crate::util::camera::Camera;
+    use crate::util::film::Film;
+    use crate::util::ray::Ray;
+    use crate::util::vector::Vector;
+
+    #[derive(Debug)]
+    struct Empty;
+
+    impl DataStructure for Empty {
+        fn intersects<'a>(&'a self, _ray: &'a Ray) -> Option<Intersection<'a>> {
+            None
+        }
+    }
+
+    /// Black on the left half of the image, white on the right half.
+    #[derive(Debug)]
+    struct Edge;
+
+    impl Shader for Edge {
+        fn shade(&self, ray: &Ray, _: &dyn DataStructure, _: &mut SampleStream) -> Vector {
+            Vector::repeated(if ray.direction.x >= 0. { 1. } else { 0. })
+        }
+    }
+
+    #[test]
+    fn test_samples_are_splatted_where_they_are_traced() {
+        let camera = Camera::new(
+            Vector::default(),
+            Vector::new(0., 0., -1.),
+            Vector::new(0., 1., 0.),
+            4,
+            1,
+            90.,
+        );
+        let tracer = MSTracer::new(1, Box::new(RandomSampler), Box::new(Tent::new(1.)));
+        let film = Film::new(4, 1);
+        for x in 0..4 {
+            tracer.raytrace(x, 0, &Empty, &Edge, &camera, &film);
         }
 
-        out / self.samples_per_pixel as f64
+        // The samples go through the top left corners of the pixels, at x = 0, 1, 2 and 3,
+        // and the edge between black and white is at 2. The tent gives every pixel half of
+        // the samples on either side of its center, so only pixel 1 is half white.
+        let output = film.to_output();
+        let colors: Vec<f64> = output[0].iter().map(|i| i.x).collect();
+        assert_eq!(colors, [0., 0.5, 1., 1.]);
     }
 }
@@ -3,6 +3,7 @@ use crate::raytracer::RayTracer;
 use crate::shader::Shader;
 use crate::util::camera::Camera;
 use crate::util::outputbuffer::OutputBuffer;
+use crate::util::rng::mix_seed;
 
 mod builder;
 
@@ -17,6 +18,8 @@ pub struct Renderer<'r> {
     shader: &'r dyn Shader,
     datastructure: &'r dyn DataStructure,
     postprocessor: &'r dyn PostProcessor,
+    /// Seeds the random numbers of every pixel of every pass, which makes renders reproducible.
+    seed: Option<u64>,
 }
 
 impl<'r> Renderer<'r> {
@@ -33,17 +36,134 @@ impl<'r> Renderer<'r> {
             shader,
             datastructure,
             postprocessor,
+            seed: None,
         }
     }
 
+    /// Renders with the same random numbers every time, whatever the generator and the number
+    /// of threads. Without a seed, every render is different.
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The seed of a pass, different for every pass so they don't repeat each other.
+    fn pass_seed(&self, pass: usize) -> Option<u64> {
+        self.seed.map(|seed| mix_seed(seed, pass as u64))
+    }
+
     pub fn render(&self, camera: &Camera) -> OutputBuffer {
         let output = self.generator.generate_internal(
             self.raytracer,
             self.datastructure,
             self.shader,
             camera,
+            self.pass_seed(0),
         );
 
         self.postprocessor.process(output)
     }
+
+    /// Renders the image `passes` times and averages the passes. After every pass `on_pass`
+    /// is called, which can be used to look at the image so far while the render continues.
+    pub fn render_progressive(
+        &self,
+        camera: &Camera,
+        passes: usize,
+        on_pass: &mut dyn FnMut(Progress),
+    ) -> OutputBuffer {
+        let mut output = OutputBuffer::with_size(camera.width, camera.height);
+
+        for pass in 0..passes {
+            let pass_output = self.generator.generate_internal(
+                self.raytracer,
+                self.datastructure,
+                self.shader,
+                camera,
+                self.pass_seed(pass),
+            );
+            output.average_in(&pass_output, pass);
+
+            on_pass(Progress {
+                passes_done: pass + 1,
+                passes,
+                output: &output,
+                postprocessor: self.postprocessor,
+            });
+        }
+
+        self.postprocessor.process(output)
+    }
+}
+
+/// The state of a progressive render after a pass has finished.
+pub struct Progress<'p> {
+    /// Number of passes that have been rendered so far.
+    pub passes_done: usize,
+    /// Number of passes that will be rendered in total.
+    pub passes: usize,
+
+    output: &'p OutputBuffer,
+    postprocessor: &'p dyn PostProcessor,
+}
+
+impl<'p> Progress<'p> {
+    /// Returns the postprocessed average of all passes so far.
+    pub fn image(&self) -> OutputBuffer {
+        self.postprocessor.process(self.output.clone())
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.passes_done == self.passes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datastructure::bvh::KDTreeDataStructure;
+    use crate::filter::tent::Tent;
+    use crate::generator::basic::BasicGenerator;
+    use crate::generator::crossbeam::CrossbeamGenerator;
+    use crate::generator::Generator;
+    use crate::raytracer::jmstracer::JMSTracer;
+    use crate::renderer::RendererBuilder;
+    use crate::sampler::stratified::Stratified;
+    use crate::scene::SceneBuilder;
+    use crate::shader::mcshader::McShader;
+    use crate::shader::roulette::RussianRoulette;
+    use crate::util::camera::Camera;
+    use crate::util::vector::Vector;
+
+    #[test]
+    fn test_seeded_renders_are_identical() {
+        let scene = SceneBuilder::new()
+            .build_from_tobj(tobj::load_obj("scenes/monte-carlo.obj".as_ref()).unwrap())
+            .unwrap();
+        let datastructure = KDTreeDataStructure::new(&scene);
+        let raytracer = JMSTracer::new(4, Box::new(Stratified), Box::new(Tent::new(1.)));
+        let shader = McShader::new(true, RussianRoulette::new(3, 32));
+        let camera = Camera::new(
+            Vector::new(0., 1., 3.),
+            Vector::new(0., 0., -1.),
+            Vector::new(0., 1., 0.),
+            12,
+            12,
+            60.,
+        );
+
+        let render = |generator: &dyn Generator, seed| {
+            RendererBuilder::new(generator)
+                .with_raytracer(&raytracer)
+                .with_shader(&shader)
+                .with_datastructure(&datastructure)
+                .without_postprocessor()
+                .with_seed(seed)
+                .render_progressive(&camera, 2, &mut |_| {})
+        };
+
+        let first = render(&BasicGenerator, Some(7));
+        assert!(*first == *render(&BasicGenerator, Some(7)));
+        assert!(*first == *render(&CrossbeamGenerator::new(3), Some(7)));
+        assert!(*first != *render(&BasicGenerator, Some(8)));
+    }
 }
@@ -0,0 +1,60 @@
+use crate::sampler::{hash, to_unit, Sampler};
+use crate::util::rng::get_rng;
+use rand::Rng;
+
+/// The bases of the dimensions of the Halton sequence. Dimensions past these are random,
+/// the sequence doesn't spread a few samples well in large bases anyway.
+const PRIMES: [u32; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131,
+];
+
+/// The Halton sequence: dimension d of sample i is the radical inverse of i in the d-th prime.
+/// Every pixel Owen scrambles the digits of every dimension. Only shifting the values would
+/// keep the first samples of the large bases in a row (i / base for i < base), so dimensions
+/// next to each other would be almost the same.
+#[derive(Debug)]
+pub struct Halton;
+
+impl Sampler for Halton {
+    fn sample_1d(&self, seed: u64, index: usize, _count: usize, dimension: usize) -> f64 {
+        match PRIMES.get(dimension) {
+            Some(&base) => scrambled_radical_inverse(index as u64, base, hash(seed, dimension)),
+            None => get_rng(|mut r| r.gen::<f64>()),
+        }
+    }
+
+    fn sample_2d(&self, seed: u64, index: usize, count: usize, dimension: usize) -> (f64, f64) {
+        (
+            self.sample_1d(seed, index, count, dimension),
+            self.sample_1d(seed, index, count, dimension + 1),
+        )
+    }
+}
+
+/// Mirrors the digits of `index` in `base` around the decimal point (0.d1d2d3... for
+/// ...d3d2d1), and Owen scrambles them: every digit is permuted based on the digits before it.
+/// The permutations are random affine maps d -> (a * d + b) mod base, which are bijective
+/// because the base is prime.
+fn scrambled_radical_inverse(mut index: u64, base: u32, seed: u32) -> f64 {
+    let inverse_base = 1. / base as f64;
+
+    let mut state = seed;
+    let mut reversed = 0u64;
+    let mut scale = 1.;
+    // The zeros in front of the index are permuted as well, at least until the digits are
+    // finer than any number of samples a pixel gets.
+    while index > 0 || scale > 1e-6 {
+        let digit = (index % base as u64) as u32;
+        let a = 1 + state % (base - 1);
+        let b = (state >> 16) % base;
+        reversed = reversed * base as u64 + ((a * digit + b) % base) as u64;
+        scale *= inverse_base;
+        index /= base as u64;
+        state = hash(state as u64, digit as usize + 1);
+    }
+
+    // The remaining digits are random as well.
+    let value = (reversed as f64 + to_unit(state)) * scale;
+    value.min(1. - f64::EPSILON)
+}
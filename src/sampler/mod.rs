@@ -0,0 +1,201 @@
+//! Samplers decide which random numbers the samples of a pixel get. Independent random
+//! numbers clump together and leave gaps, so a pixel converges slowly. The other samplers
+//! spread the samples of a pixel evenly over every dimension (the position in the pixel,
+//! the point on the lens, the direction of every bounce and so on), which gives less noise
+//! for the same number of samples.
+use std::fmt::Debug;
+
+pub mod halton;
+pub mod random;
+pub mod sobol;
+pub mod stratified;
+
+use crate::sampler::random::RandomSampler;
+use crate::util::rng::mix_seed;
+
+/// A sampler gives the value of one dimension of one sample of a pixel. The values of a
+/// dimension are spread over [0, 1) across the `count` samples of the pixel.
+///
+/// `seed` is different for every pixel (and every pass), and randomizes the values so that
+/// neighbouring pixels don't get the same pattern.
+pub trait Sampler: Send + Sync + Debug {
+    fn sample_1d(&self, seed: u64, index: usize, count: usize, dimension: usize) -> f64;
+
+    /// Two dimensions that are also spread evenly over the unit square together,
+    /// for things like directions and points on a lens.
+    fn sample_2d(&self, seed: u64, index: usize, count: usize, dimension: usize) -> (f64, f64);
+}
+
+/// The random numbers of a single sample of a pixel. Every call takes the next dimension,
+/// so the same step of a path (for example the second bounce) always uses the same dimension.
+pub struct SampleStream<'s> {
+    sampler: &'s dyn Sampler,
+    seed: u64,
+    index: usize,
+    count: usize,
+    dimension: usize,
+}
+
+impl<'s> SampleStream<'s> {
+    /// Sample `index` of the `count` samples of the pixel identified by `seed`.
+    pub fn new(sampler: &'s dyn Sampler, seed: u64, index: usize, count: usize) -> Self {
+        Self {
+            sampler,
+            seed,
+            index,
+            count,
+            dimension: 0,
+        }
+    }
+
+    /// A stream of independent random numbers.
+    pub fn random() -> SampleStream<'static> {
+        SampleStream::new(&RandomSampler, 0, 0, 1)
+    }
+
+    pub fn next_1d(&mut self) -> f64 {
+        let value = self
+            .sampler
+            .sample_1d(self.seed, self.index, self.count, self.dimension);
+        self.dimension += 1;
+        value
+    }
+
+    pub fn next_2d(&mut self) -> (f64, f64) {
+        let value = self
+            .sampler
+            .sample_2d(self.seed, self.index, self.count, self.dimension);
+        self.dimension += 2;
+        value
+    }
+
+    /// The dimension the next call takes.
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Continues at `dimension`, leaving the dimensions before it unused. Steps that take
+    /// a varying number of dimensions use this to start the next step at a fixed dimension.
+    pub fn skip_to(&mut self, dimension: usize) {
+        debug_assert!(dimension >= self.dimension, "the stream can't go back");
+        self.dimension = dimension;
+    }
+}
+
+/// Mixes the seed of a pixel with a dimension, so every dimension is randomized independently.
+fn hash(seed: u64, dimension: usize) -> u32 {
+    mix_seed(seed, dimension as u64) as u32
+}
+
+/// Maps all 32 bits of `x` to a float in [0, 1).
+fn to_unit(x: u32) -> f64 {
+    x as f64 / 4_294_967_296.
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sampler::halton::Halton;
+    use crate::sampler::random::RandomSampler;
+    use crate::sampler::sobol::Sobol;
+    use crate::sampler::stratified::Stratified;
+    use crate::sampler::Sampler;
+
+    /// The largest distance between the fraction of `points` below `x` and `x` itself.
+    fn discrepancy(points: &mut [f64]) -> f64 {
+        points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let n = points.len() as f64;
+
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x - i as f64 / n).abs().max((x - (i + 1) as f64 / n).abs()))
+            .fold(0., f64::max)
+    }
+
+    #[test]
+    fn test_samplers_are_evenly_spread() {
+        let count = 64;
+        let samplers: [&dyn Sampler; 5] = [
+            &RandomSampler,
+            &Stratified,
+            &Halton,
+            &Sobol::shifted(),
+            &Sobol::owen(),
+        ];
+
+        // Halton only spreads a few samples well in the small bases of the first dimensions.
+        let spread_dimensions: [(&dyn Sampler, &[usize]); 4] = [
+            (samplers[1], &[0, 1, 7, 20]),
+            (samplers[2], &[0, 1]),
+            (samplers[3], &[0, 1, 7, 20]),
+            (samplers[4], &[0, 1, 7, 20]),
+        ];
+        for (sampler, dimensions) in &spread_dimensions {
+            for dimension in *dimensions {
+                let mut points: Vec<f64> = (0..count)
+                    .map(|i| sampler.sample_1d(1234, i, count, *dimension))
+                    .collect();
+                assert!(points.iter().all(|i| (0. ..1.).contains(i)));
+                // Random points are typically ~0.1 off, evenly spread ones at most 1/count.
+                assert!(
+                    discrepancy(&mut points) <= 2. / count as f64,
+                    "{:?} dimension {}",
+                    sampler,
+                    dimension
+                );
+            }
+        }
+
+        // In two dimensions, every cell of an 8 by 8 grid gets exactly one of the 64 samples.
+        for sampler in &[&samplers[1], &samplers[3], &samplers[4]] {
+            for dimension in &[0, 5, 40] {
+                let mut cells = [0; 64];
+                for i in 0..count {
+                    let (x, y) = sampler.sample_2d(1234, i, count, *dimension);
+                    cells[(y * 8.) as usize * 8 + (x * 8.) as usize] += 1;
+                }
+                assert!(cells.iter().all(|&i| i == 1), "{:?}", sampler);
+            }
+        }
+
+        for sampler in &samplers {
+            // Different pixels get different values.
+            assert_ne!(
+                sampler.sample_1d(1, 0, count, 0),
+                sampler.sample_1d(2, 0, count, 0)
+            );
+        }
+    }
+
+    /// The correlation coefficient of the points in `xs` and `ys`.
+    fn correlation(xs: &[f64], ys: &[f64]) -> f64 {
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+        let (mean_x, mean_y) = (mean(xs), mean(ys));
+        let covariance = |a: &[f64], mean_a: f64, b: &[f64], mean_b: f64| {
+            a.iter()
+                .zip(b)
+                .map(|(a, b)| (a - mean_a) * (b - mean_b))
+                .sum::<f64>()
+        };
+
+        covariance(xs, mean_x, ys, mean_y)
+            / (covariance(xs, mean_x, xs, mean_x) * covariance(ys, mean_y, ys, mean_y)).sqrt()
+    }
+
+    #[test]
+    fn test_halton_dimensions_are_independent() {
+        // Unscrambled, the first samples of the large bases are i / base, so dimension 20
+        // (base 73) and 21 (base 79) would almost be the same, with a correlation of about 1.
+        let count = 16;
+        let mut total = 0.;
+        for seed in 0..100 {
+            let (xs, ys): (Vec<f64>, Vec<f64>) = (0..count)
+                .map(|i| Halton.sample_2d(seed, i, count, 20))
+                .unzip();
+            total += correlation(&xs, &ys).abs();
+        }
+
+        // Independent dimensions average about 0.2.
+        assert!(total / 100. < 0.35, "{}", total / 100.);
+    }
+}
@@ -0,0 +1,17 @@
+use crate::sampler::Sampler;
+use crate::util::rng::get_rng;
+use rand::Rng;
+
+/// Independent random numbers for every sample and dimension, like plain Monte Carlo.
+#[derive(Debug)]
+pub struct RandomSampler;
+
+impl Sampler for RandomSampler {
+    fn sample_1d(&self, _seed: u64, _index: usize, _count: usize, _dimension: usize) -> f64 {
+        get_rng(|mut r| r.gen::<f64>())
+    }
+
+    fn sample_2d(&self, _seed: u64, _index: usize, _count: usize, _dimension: usize) -> (f64, f64) {
+        get_rng(|mut r| (r.gen::<f64>(), r.gen::<f64>()))
+    }
+}
@@ -0,0 +1,85 @@
+use crate::sampler::{hash, to_unit, Sampler};
+
+/// The first two dimensions of the Sobol sequence. Every call (every pair of dimensions)
+/// shuffles the order of the samples differently, so the pairs aren't correlated with
+/// each other ("padding", as in "Practical Hash-based Owen Scrambling" by Brent Burley).
+#[derive(Debug)]
+pub struct Sobol {
+    /// Randomize the points with Owen scrambling instead of a random xor. Owen scrambled
+    /// points stay evenly spread at every scale, so the noise looks like blue noise.
+    owen: bool,
+}
+
+impl Sobol {
+    /// Randomized by xoring every dimension with a random number (a digital shift).
+    pub fn shifted() -> Self {
+        Self { owen: false }
+    }
+
+    /// Randomized with hash based Owen scrambling.
+    pub fn owen() -> Self {
+        Self { owen: true }
+    }
+
+    fn randomize(&self, value: u32, seed: u32) -> f64 {
+        if self.owen {
+            to_unit(nested_uniform_scramble(value, seed))
+        } else {
+            to_unit(value ^ seed)
+        }
+    }
+
+    /// The index of sample `index` after shuffling the samples for `dimension`.
+    fn shuffled_index(seed: u64, index: usize, dimension: usize) -> u32 {
+        nested_uniform_scramble(index as u32, hash(seed, 3 * dimension))
+    }
+}
+
+impl Sampler for Sobol {
+    fn sample_1d(&self, seed: u64, index: usize, _count: usize, dimension: usize) -> f64 {
+        let index = Self::shuffled_index(seed, index, dimension);
+        self.randomize(index.reverse_bits(), hash(seed, 3 * dimension + 1))
+    }
+
+    fn sample_2d(&self, seed: u64, index: usize, _count: usize, dimension: usize) -> (f64, f64) {
+        let (x, y) = sobol_2d(Self::shuffled_index(seed, index, dimension));
+        (
+            self.randomize(x, hash(seed, 3 * dimension + 1)),
+            self.randomize(y, hash(seed, 3 * dimension + 2)),
+        )
+    }
+}
+
+/// The first two dimensions of the Sobol sequence as 32 bit fractions. The first is the
+/// van der Corput sequence, the second uses the direction numbers v_(k+1) = v_k ^ (v_k >> 1).
+fn sobol_2d(mut index: u32) -> (u32, u32) {
+    let x = index.reverse_bits();
+
+    let mut y = 0;
+    let mut direction = 1 << 31;
+    while index != 0 {
+        if index & 1 != 0 {
+            y ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+
+    (x, y)
+}
+
+/// Owen scrambles the bits of `x` from the most significant bit down: every bit is flipped
+/// based on the bits above it. From "Practical Hash-based Owen Scrambling" by Brent Burley.
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+/// A hash in which every bit only depends on the bits below it.
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+    x
+}
@@ -0,0 +1,75 @@
+use crate::sampler::{hash, Sampler};
+use crate::util::rng::get_rng;
+use rand::Rng;
+
+/// Jittered stratification: every dimension is split into `count` equal strata and every
+/// sample gets a random point in its own stratum. Which sample gets which stratum is
+/// shuffled differently for every dimension, so the dimensions aren't correlated.
+/// In two dimensions the square is split into a grid, which needs a square number of
+/// samples to cover all of it. Samples that don't fit in the grid are random.
+#[derive(Debug)]
+pub struct Stratified;
+
+impl Sampler for Stratified {
+    fn sample_1d(&self, seed: u64, index: usize, count: usize, dimension: usize) -> f64 {
+        let stratum = permute(index as u32, count as u32, hash(seed, dimension));
+        let jitter = get_rng(|mut r| r.gen::<f64>());
+
+        (stratum as f64 + jitter) / count as f64
+    }
+
+    fn sample_2d(&self, seed: u64, index: usize, count: usize, dimension: usize) -> (f64, f64) {
+        let size = (count as f64).sqrt() as usize;
+        let (jx, jy) = get_rng(|mut r| (r.gen::<f64>(), r.gen::<f64>()));
+        if index >= size * size {
+            return (jx, jy);
+        }
+
+        let cell = permute(index as u32, (size * size) as u32, hash(seed, dimension)) as usize;
+        (
+            ((cell % size) as f64 + jx) / size as f64,
+            ((cell / size) as f64 + jy) / size as f64,
+        )
+    }
+}
+
+/// A random permutation of 0..length, picked by `seed`, evaluated at `index`.
+/// From "Correlated Multi-Jittered Sampling" by Andrew Kensler.
+pub(super) fn permute(mut index: u32, length: u32, seed: u32) -> u32 {
+    if length <= 1 {
+        return 0;
+    }
+
+    let mut mask = length - 1;
+    mask |= mask >> 1;
+    mask |= mask >> 2;
+    mask |= mask >> 4;
+    mask |= mask >> 8;
+    mask |= mask >> 16;
+
+    // Permutes 0..=mask, and tries again until the result falls in 0..length.
+    loop {
+        index ^= seed;
+        index = index.wrapping_mul(0xe170_893d);
+        index ^= seed >> 16;
+        index ^= (index & mask) >> 4;
+        index ^= seed >> 8;
+        index = index.wrapping_mul(0x0929_eb3f);
+        index ^= seed >> 23;
+        index ^= (index & mask) >> 1;
+        index = index.wrapping_mul(1 | seed >> 27);
+        index = index.wrapping_mul(0x6935_fa69);
+        index ^= (index & mask) >> 11;
+        index = index.wrapping_mul(0x74dc_b303);
+        index ^= (index & mask) >> 2;
+        index = index.wrapping_mul(0x9e50_1cc3);
+        index ^= (index & mask) >> 2;
+        index = index.wrapping_mul(0xc860_a3df);
+        index &= mask;
+        index ^= index >> 5;
+
+        if index < length {
+            return index.wrapping_add(seed) % length;
+        }
+    }
+}
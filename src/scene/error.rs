@@ -1,17 +1,49 @@
 use crate::scene::light::LightError;
 use crate::scene::texture::TextureError;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
 
 #[derive(Debug)]
 pub enum SceneError {
-    TextureError(TextureError),
+    TextureError {
+        /// Name of the material the texture belongs to
+        material: String,
+        /// The MTL key the texture was specified with, for example `map_Kd`
+        slot: &'static str,
+        error: TextureError,
+    },
     LightError(LightError),
+    /// A motion was given for a mesh that isn't in the scene.
+    UnknownMesh(String),
 }
 
-impl From<TextureError> for SceneError {
-    fn from(t: TextureError) -> Self {
-        SceneError::TextureError(t)
+impl Display for SceneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::TextureError { material, slot, .. } => write!(
+                f,
+                "couldn't load the {} texture of material '{}'",
+                slot, material
+            ),
+            SceneError::LightError(_) => write!(f, "couldn't set up the light sources"),
+            SceneError::UnknownMesh(name) => {
+                write!(f, "the scene has no mesh called '{}' to move", name)
+            }
+        }
     }
 }
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::TextureError { error, .. } => Some(error),
+            SceneError::LightError(e) => Some(e),
+            SceneError::UnknownMesh(_) => None,
+        }
+    }
+}
+
 impl From<LightError> for SceneError {
     fn from(l: LightError) -> Self {
         SceneError::LightError(l)
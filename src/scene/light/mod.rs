@@ -1,21 +1,48 @@
-use crate::scene::Scene;
 use crate::scene::triangle::Triangle;
-use crate::util::rng::get_rng;
-use rand::distributions::weighted::alias_method::WeightedIndex;
-use rand::distributions::WeightedError;
-use rand::Rng;
-use serde::export::fmt::Debug;
-use serde::export::Formatter;
 use core::fmt;
+use rand::distributions::WeightedError;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
 
 #[derive(Debug)]
 pub enum LightError {
     WeightedError(WeightedError),
 }
 
+impl Display for LightError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LightError::WeightedError(WeightedError::NoItem) => write!(
+                f,
+                "the scene has no emissive triangles (no material with a non-zero Ke)"
+            ),
+            LightError::WeightedError(_) => {
+                write!(f, "couldn't weigh the light sources by their emittance")
+            }
+        }
+    }
+}
+
+impl Error for LightError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            // The message already says what went wrong.
+            LightError::WeightedError(WeightedError::NoItem) => None,
+            LightError::WeightedError(e) => Some(e),
+        }
+    }
+}
+
+/// Keeps track of all emissive triangles in a scene, so shaders can pick one
+/// weighted by how much light it emits. A scene without emissive triangles is valid,
+/// in that case there are simply no sources to pick from.
 pub struct LightSourceManager<'l> {
     lightsources: Vec<&'l Triangle<'l>>,
-    weights: WeightedIndex<f64>,
+    /// The sum of the weights of the light sources up to and including each one,
+    /// so a uniform number times the total weight can be looked up with a binary search.
+    cumulative_weights: Vec<f64>,
+    /// Sum of the weights of all light sources.
+    total_weight: f64,
 }
 
 impl<'l> Debug for LightSourceManager<'l> {
@@ -25,10 +52,6 @@ impl<'l> Debug for LightSourceManager<'l> {
 }
 
 impl<'l> LightSourceManager<'l> {
-    pub fn new(scene: &'l Scene) -> Result<Self, LightError> {
-        Self::from_triangle_iter(scene.triangles())
-    }
-
     pub(super) fn from_triangle_iter(
         iter: impl Iterator<Item = &'l Triangle<'l>>,
     ) -> Result<Self, LightError> {
@@ -36,27 +59,59 @@ impl<'l> LightSourceManager<'l> {
             .filter(|i| !i.mesh.material.emittance.iszero())
             .collect();
 
-        let weights = WeightedIndex::new(
-            lightsources
-                .iter()
-                .map(|l| {
-                    let area = l.area();
-                    let emittance = l.mesh.material.emittance.length();
+        let mut cumulative_weights = Vec::with_capacity(lightsources.len());
+        let mut total_weight = 0.;
+        for light in &lightsources {
+            let weight = Self::weight(light);
+            if weight < 0. || !weight.is_finite() {
+                return Err(LightError::WeightedError(WeightedError::InvalidWeight));
+            }
+
+            total_weight += weight;
+            cumulative_weights.push(total_weight);
+        }
 
-                    (area * emittance) as f64
-                })
-                .collect(),
-        )
-        .map_err(LightError::WeightedError)?;
+        if !lightsources.is_empty() && total_weight <= 0. {
+            return Err(LightError::WeightedError(WeightedError::AllWeightsZero));
+        }
 
         Ok(Self {
             lightsources,
-            weights,
+            cumulative_weights,
+            total_weight,
         })
     }
 
-    pub fn random_source(&self) -> &'l Triangle {
-        let index = get_rng(|mut r| r.sample(&self.weights));
-        self.lightsources[index]
+    /// The weight with which a triangle is picked: area × emittance.
+    fn weight(triangle: &Triangle) -> f64 {
+        let area = triangle.area();
+        let emittance = triangle.mesh.material.emittance.length();
+
+        area * emittance
+    }
+
+    /// The probability that `sample_source` picks this triangle.
+    /// Zero for triangles that don't emit light.
+    pub fn pdf(&self, triangle: &Triangle) -> f64 {
+        if self.total_weight <= 0. || triangle.mesh.material.emittance.iszero() {
+            return 0.;
+        }
+
+        Self::weight(triangle) / self.total_weight
+    }
+
+    /// Returns true if the scene has no emissive triangles.
+    pub fn is_empty(&self) -> bool {
+        self.lightsources.is_empty()
+    }
+
+    /// Picks a light source with a uniform number `u` in [0, 1), weighted by area × emittance.
+    /// Returns None if there are no light sources in the scene.
+    pub fn sample_source(&self, u: f64) -> Option<&'l Triangle<'l>> {
+        let target = u * self.total_weight;
+        let index = self.cumulative_weights.partition_point(|&i| i <= target);
+        // u is below 1, but rounding can still put the target at the total weight.
+        let index = index.min(self.lightsources.len().checked_sub(1)?);
+        self.lightsources.get(index).copied()
     }
 }
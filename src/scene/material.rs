@@ -10,8 +10,8 @@ lazy_static! {
         diffuse: Vector::default(),
         specular: Vector::default(),
         shininess: 0.0,
-        dissolve: 0.0,
-        optical_density: 0.0,
+        dissolve: 1.0,
+        optical_density: 1.0,
         ambient_texture: None,
         diffuse_texture: None,
         specular_texture: None,
@@ -27,6 +27,7 @@ lazy_static! {
 #[derive(Debug)]
 pub struct Material<'m> {
     /// Material name as specified in the MTL file
+    #[allow(dead_code)]
     pub name: String,
     /// Ambient color of the material
     pub ambient: Vector,
@@ -54,6 +55,7 @@ pub struct Material<'m> {
     pub specular_texture: Option<&'m Texture>,
     /// Name of the normal map texture file for the material. No path is pre-pended to the texture
     /// file names specified in the MTL file
+    #[allow(dead_code)]
     pub normal_texture: Option<&'m Texture>,
     /// Name of the alpha map texture file for the material. No path is pre-pended to the texture
     /// file names specified in the MTL file. Referred to as dissolve to match the MTL file format
@@ -104,26 +106,26 @@ impl<'m> Material<'m> {
             shininess: material.shininess as f64,
             dissolve: material.dissolve as f64,
             optical_density: material.optical_density as f64,
-            ambient_texture: mem::transmute::<_, Option<&'m Texture>>(
+            ambient_texture: mem::transmute::<Option<&Texture>, Option<&'m Texture>>(
                 textureatlas.get_texture(&material.ambient_texture),
             ),
-            diffuse_texture: mem::transmute::<_, Option<&'m Texture>>(
+            diffuse_texture: mem::transmute::<Option<&Texture>, Option<&'m Texture>>(
                 textureatlas.get_texture(&material.diffuse_texture),
             ),
-            specular_texture: mem::transmute::<_, Option<&'m Texture>>(
+            specular_texture: mem::transmute::<Option<&Texture>, Option<&'m Texture>>(
                 textureatlas.get_texture(&material.specular_texture),
             ),
-            normal_texture: mem::transmute::<_, Option<&'m Texture>>(
+            normal_texture: mem::transmute::<Option<&Texture>, Option<&'m Texture>>(
                 textureatlas.get_texture(&material.normal_texture),
             ),
-            dissolve_texture: mem::transmute::<_, Option<&'m Texture>>(
+            dissolve_texture: mem::transmute::<Option<&Texture>, Option<&'m Texture>>(
                 textureatlas.get_texture(&material.dissolve_texture),
             ),
             illumination_model: material.illumination_model,
 
             emittance,
-            emittance_texture: mem::transmute::<_, Option<&'m Texture>>(
-                textureatlas.get_texture(&emittance_texture_name),
+            emittance_texture: mem::transmute::<Option<&Texture>, Option<&'m Texture>>(
+                textureatlas.get_texture(emittance_texture_name),
             ),
         }
     }
@@ -1,6 +1,7 @@
 pub mod error;
 pub mod light;
 pub mod material;
+pub mod motion;
 pub mod texture;
 pub mod texturecoordinate;
 pub mod triangle;
@@ -9,12 +10,15 @@ use crate::scene::error::SceneError;
 use crate::scene::light::LightSourceManager;
 use crate::scene::material::Material;
 use crate::scene::material::DEFAULT_MATERIAL;
+use crate::scene::motion::{Motion, Transform};
 use crate::scene::texture::{TextureAtlas, TextureAtlasBuilder};
 use crate::scene::texturecoordinate::TextureCoordinate;
 use crate::scene::triangle::Triangle;
 use crate::util::vector::Vector;
-use serde::export::fmt::Debug;
-use serde::export::Formatter;
+use log::warn;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
 use std::path::Path;
 use std::pin::Pin;
 use std::{fmt, mem};
@@ -29,6 +33,9 @@ pub struct Mesh<'m> {
 
     pub material: &'m Material<'m>,
 
+    /// Meshes without motion stay where they are during the exposure.
+    pub motion: Option<Motion>,
+
     // Private by design. This option is actually always Some()
     lightsourcemanager: Option<Arc<LightSourceManager<'m>>>
 }
@@ -47,6 +54,7 @@ impl<'m> Default for Mesh<'m> {
             triangles: vec![].into_boxed_slice(),
             texcoords: vec![].into_boxed_slice(),
             material: &DEFAULT_MATERIAL,
+            motion: None,
             lightsourcemanager: None,
         }
     }
@@ -74,7 +82,7 @@ impl<'s> Debug for Scene<'s> {
 }
 
 impl<'s> Scene<'s> {
-    pub fn triangles(&self) -> impl Iterator<Item = &Triangle> {
+    pub fn triangles(&self) -> impl Iterator<Item = &Triangle<'_>> {
         self.meshes.iter().flat_map(move |i| i.triangles.iter())
     }
 
@@ -98,12 +106,16 @@ impl<'s> Scene<'s> {
 pub struct SceneBuilder<'s> {
     /// This path is used to search for texture files.
     texturepath: &'s Path,
+
+    /// The start and end transforms of moving meshes, by mesh name.
+    motions: HashMap<String, (Transform, Transform)>,
 }
 
 impl<'s> SceneBuilder<'s> {
     pub fn new() -> Self {
         Self {
             texturepath: Path::new(""),
+            motions: HashMap::new(),
         }
     }
 
@@ -112,6 +124,26 @@ impl<'s> SceneBuilder<'s> {
         self
     }
 
+    /// Moves the meshes (OBJ objects or groups) called `name` from `start` at time 0
+    /// to `end` at time 1. They rotate around the center of all meshes with that name.
+    pub fn motion(mut self, name: impl Into<String>, start: Transform, end: Transform) -> Self {
+        self.motions.insert(name.into(), (start, end));
+        self
+    }
+
+    /// The center of the bounding box of every model called `name`.
+    fn pivot(models: &[tobj::Model], name: &str) -> Option<Vector> {
+        let mut positions = models
+            .iter()
+            .filter(|i| i.name == name)
+            .flat_map(|i| i.mesh.positions.chunks_exact(3))
+            .map(|i| Vector::new(i[0] as f64, i[1] as f64, i[2] as f64));
+
+        let first = positions.next()?;
+        let (min, max) = positions.fold((first, first), |(min, max), i| (min.min(&i), max.max(&i)));
+        Some((min + max) / 2.)
+    }
+
     pub fn build_from_tobj<'a>(
         &self,
         (models, tobjmaterials): (Vec<tobj::Model>, Vec<tobj::Material>),
@@ -124,32 +156,38 @@ impl<'s> SceneBuilder<'s> {
         };
         let mut textureatlasbuilder = TextureAtlasBuilder::new();
 
-        for material in &tobjmaterials {
-            if !material.diffuse_texture.is_empty() {
-                textureatlasbuilder
-                    .add_texture_file(&material.diffuse_texture, &self.texturepath)?
-            }
-            if !material.ambient_texture.is_empty() {
-                textureatlasbuilder
-                    .add_texture_file(&material.ambient_texture, &self.texturepath)?
-            }
-            if !material.dissolve_texture.is_empty() {
-                textureatlasbuilder
-                    .add_texture_file(&material.dissolve_texture, &self.texturepath)?
-            }
-            if !material.specular_texture.is_empty() {
-                textureatlasbuilder
-                    .add_texture_file(&material.specular_texture, &self.texturepath)?
-            }
+        let mut motions = HashMap::new();
+        for (name, (start, end)) in &self.motions {
+            let pivot =
+                Self::pivot(&models, name).ok_or_else(|| SceneError::UnknownMesh(name.clone()))?;
+            motions.insert(name.as_str(), Motion::new(*start, *end, pivot));
+        }
 
+        for material in &tobjmaterials {
             let default_emittance_texture_name = "".into();
             let emittance_texture_name = material
                 .unknown_param
                 .get("map_Ke")
                 .unwrap_or(&default_emittance_texture_name);
 
-            if !emittance_texture_name.is_empty() {
-                textureatlasbuilder.add_texture_file(emittance_texture_name, &self.texturepath)?
+            let textures = [
+                ("map_Kd", &material.diffuse_texture),
+                ("map_Ka", &material.ambient_texture),
+                ("map_d", &material.dissolve_texture),
+                ("map_Ks", &material.specular_texture),
+                ("map_Ke", emittance_texture_name),
+            ];
+
+            for (slot, filename) in textures.iter() {
+                if !filename.is_empty() {
+                    textureatlasbuilder
+                        .add_texture_file(filename, self.texturepath)
+                        .map_err(|error| SceneError::TextureError {
+                            material: material.name.clone(),
+                            slot,
+                            error,
+                        })?
+                }
             }
         }
 
@@ -205,6 +243,7 @@ impl<'s> SceneBuilder<'s> {
                 normals: normals.collect::<Vec<_>>().into_boxed_slice(),
                 texcoords: texcoords.collect::<Vec<_>>().into_boxed_slice(),
                 material,
+                motion: motions.get(model.name.as_str()).cloned(),
                 lightsourcemanager: None,
             }
         }
@@ -219,6 +258,10 @@ impl<'s> SceneBuilder<'s> {
                 }),
         )?);
 
+        if lightsourcemanager.is_empty() {
+            warn!("The scene has no emissive triangles. It is only lit by what the shader adds itself");
+        }
+
         for i in meshes.iter_mut() {
             i.lightsourcemanager = Some(lightsourcemanager.clone())
         }
@@ -231,3 +274,109 @@ impl<'s> SceneBuilder<'s> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::scene::motion::Transform;
+    use crate::scene::{Scene, SceneBuilder};
+    use crate::util::vector::Vector;
+    use std::path::Path;
+
+    fn load(filename: &str) -> Scene<'static> {
+        SceneBuilder::new()
+            .texturepath(Path::new("scenes"))
+            .build_from_tobj(tobj::load_obj(filename.as_ref()).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_unlit_scene_loads() {
+        let scene = load("scenes/cube.obj");
+
+        assert!(scene.triangles().count() > 0);
+        for mesh in scene.meshes.iter() {
+            assert!(mesh.lightsourcemanager().is_empty());
+            assert!(mesh.lightsourcemanager().sample_source(0.5).is_none());
+        }
+    }
+
+    #[test]
+    fn test_lit_scene_has_sources() {
+        let scene = load("scenes/monte-carlo.obj");
+        let lightsourcemanager = scene.meshes[0].lightsourcemanager();
+
+        assert!(!lightsourcemanager.is_empty());
+        let source = lightsourcemanager.sample_source(0.5).unwrap();
+        assert!(!source.material().emittance.iszero());
+    }
+
+    #[test]
+    fn test_motion() {
+        let translation = Vector::new(1., 2., 3.);
+        let scene = SceneBuilder::new()
+            .motion(
+                "Plane",
+                Transform::default(),
+                Transform {
+                    translation,
+                    rotation: Vector::default(),
+                },
+            )
+            .build_from_tobj(tobj::load_obj("scenes/monte-carlo.obj".as_ref()).unwrap())
+            .unwrap();
+
+        let mut moving = 0;
+        for triangle in scene.triangles() {
+            let [a, _, _] = triangle.vertices_at(0.5);
+            if triangle.mesh.motion.is_some() {
+                moving += 1;
+                assert_eq!(a, triangle.a() + translation * 0.5);
+            } else {
+                assert_eq!(a, triangle.a());
+            }
+        }
+        assert!(moving > 0);
+
+        let missing = SceneBuilder::new()
+            .motion("Teapot", Transform::default(), Transform::default())
+            .build_from_tobj(tobj::load_obj("scenes/monte-carlo.obj".as_ref()).unwrap());
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_swept_vertices_cover_the_motion() {
+        let scene = SceneBuilder::new()
+            .motion(
+                "Plane",
+                Transform::default(),
+                Transform {
+                    translation: Vector::new(0., 1., 0.),
+                    rotation: Vector::new(0., 180., 30.),
+                },
+            )
+            .build_from_tobj(tobj::load_obj("scenes/monte-carlo.obj".as_ref()).unwrap())
+            .unwrap();
+
+        let mut bulges = false;
+        for triangle in scene.triangles().filter(|i| i.mesh.motion.is_some()) {
+            let (vertices, margin) = triangle.swept_vertices();
+            let (min, max) = vertices.fold(
+                (
+                    Vector::repeated(f64::INFINITY),
+                    Vector::repeated(f64::NEG_INFINITY),
+                ),
+                |(min, max), i| (min.min(&i), max.max(&i)),
+            );
+
+            for step in 0..=1000 {
+                for i in &triangle.vertices_at(step as f64 / 1000.) {
+                    let outside = (min - *i).max(&(*i - max)).max_item();
+                    // Between the steps the vertices leave the box, but never by more than the margin.
+                    bulges |= outside > 0.;
+                    assert!(outside <= margin, "{} > {}", outside, margin);
+                }
+            }
+        }
+        assert!(bulges);
+    }
+}
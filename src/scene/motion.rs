@@ -0,0 +1,169 @@
+use crate::util::vector::Vector;
+use std::cell::Cell;
+
+/// The number of steps at which a moving triangle is placed to find every point it passes.
+/// In between the steps it can leave the positions at the steps by `Motion::step_margin`.
+pub const MOTION_STEPS: usize = 16;
+
+thread_local! {
+    /// The last placement computed on this thread, with the motion and time it was for.
+    /// A ray tests many triangles of the same mesh at the same time, so this saves
+    /// computing the rotation for every one of them.
+    static LAST_PLACEMENT: Cell<Option<(Motion, f64, Placement)>> = const { Cell::new(None) };
+}
+
+/// A rigid transform: a rotation around the pivot of the mesh, followed by a translation.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Transform {
+    pub translation: Vector,
+    /// Rotation in degrees around the x, y and z axis, applied in that order.
+    pub rotation: Vector,
+}
+
+/// Moves a mesh from the `start` transform at time 0 to the `end` transform at time 1.
+/// Both transforms are linearly interpolated in between.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    pub start: Transform,
+    pub end: Transform,
+    /// The point the mesh rotates around, usually its center.
+    pub pivot: Vector,
+}
+
+impl Motion {
+    pub fn new(start: Transform, end: Transform, pivot: Vector) -> Self {
+        Self { start, end, pivot }
+    }
+
+    /// Where the mesh is at `time`. Times outside of [0, 1] are clamped.
+    pub fn at(&self, time: f64) -> Placement {
+        let time = time.clamp(0., 1.);
+        LAST_PLACEMENT.with(|last| match last.get() {
+            Some((motion, last_time, placement)) if last_time == time && motion == *self => {
+                placement
+            }
+            _ => {
+                let placement = self.compute(time);
+                last.set(Some((*self, time, placement)));
+                placement
+            }
+        })
+    }
+
+    fn compute(&self, time: f64) -> Placement {
+        let lerp = |a: Vector, b: Vector| a * (1. - time) + b * time;
+
+        let rotation = lerp(self.start.rotation, self.end.rotation);
+        let sincos = [
+            rotation.x.to_radians().sin_cos(),
+            rotation.y.to_radians().sin_cos(),
+            rotation.z.to_radians().sin_cos(),
+        ];
+
+        Placement {
+            axes: [
+                rotate(Vector::new(1., 0., 0.), sincos),
+                rotate(Vector::new(0., 1., 0.), sincos),
+                rotate(Vector::new(0., 0., 1.), sincos),
+            ],
+            pivot: self.pivot,
+            translation: lerp(self.start.translation, self.end.translation),
+        }
+    }
+
+    /// How far a point at `radius` from the pivot can get from where it is at the
+    /// nearest of the `MOTION_STEPS + 1` evenly spread times at which moving triangles
+    /// are placed.
+    pub fn step_margin(&self, radius: f64) -> f64 {
+        // Rotating around every axis moves the point at most the angle times the radius,
+        // whatever the order of the rotations.
+        let rotation = self.end.rotation - self.start.rotation;
+        let angle = (rotation.x.abs() + rotation.y.abs() + rotation.z.abs()).to_radians();
+        let distance = radius * angle + (self.end.translation - self.start.translation).length();
+
+        // The angles and the translation change linearly with time, so in a part of the time
+        // the point moves at most that part of the distance. No time is more than half a
+        // step away from the nearest step.
+        distance / (2 * MOTION_STEPS) as f64
+    }
+}
+
+/// Rotates `v` around the x, y and z axis in that order, given the sine and cosine
+/// of every angle.
+fn rotate(v: Vector, [(sx, cx), (sy, cy), (sz, cz)]: [(f64, f64); 3]) -> Vector {
+    let v = Vector::new(v.x, v.y * cx - v.z * sx, v.y * sx + v.z * cx);
+    let v = Vector::new(v.x * cy + v.z * sy, v.y, -v.x * sy + v.z * cy);
+    Vector::new(v.x * cz - v.y * sz, v.x * sz + v.y * cz, v.z)
+}
+
+/// A `Motion` evaluated at one point in time.
+#[derive(Debug, Clone, Copy)]
+pub struct Placement {
+    /// Where the x, y and z axis end up after the rotation.
+    axes: [Vector; 3],
+    pivot: Vector,
+    translation: Vector,
+}
+
+impl Placement {
+    pub fn point(&self, point: Vector) -> Vector {
+        self.vector(point - self.pivot) + self.pivot + self.translation
+    }
+
+    /// Only rotates, for directions such as normals.
+    pub fn vector(&self, vector: Vector) -> Vector {
+        self.axes[0] * vector.x + self.axes[1] * vector.y + self.axes[2] * vector.z
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scene::motion::{Motion, Transform};
+    use crate::util::vector::Vector;
+
+    fn assert_close(a: Vector, b: Vector) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_motion() {
+        let motion = Motion::new(
+            Transform::default(),
+            Transform {
+                translation: Vector::new(2., 0., 0.),
+                rotation: Vector::new(0., 0., 180.),
+            },
+            Vector::new(0., 1., 0.),
+        );
+
+        let point = Vector::new(1., 1., 0.);
+        assert_close(motion.at(0.).point(point), point);
+        assert_close(motion.at(-1.).point(point), point);
+
+        // A quarter turn around the pivot, and half of the translation.
+        assert_close(motion.at(0.5).point(point), Vector::new(1., 2., 0.));
+        assert_close(motion.at(1.).point(point), Vector::new(1., 1., 0.));
+        assert_close(
+            motion.at(0.5).vector(Vector::new(1., 0., 0.)),
+            Vector::new(0., 1., 0.),
+        );
+    }
+
+    #[test]
+    fn test_rotation_order() {
+        let motion = Motion::new(
+            Transform::default(),
+            Transform {
+                translation: Vector::default(),
+                rotation: Vector::new(90., 90., 0.),
+            },
+            Vector::default(),
+        );
+
+        // x first takes y to z, then y takes z to x.
+        assert_close(
+            motion.at(1.).vector(Vector::new(0., 1., 0.)),
+            Vector::new(1., 0., 0.),
+        );
+    }
+}
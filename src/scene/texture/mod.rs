@@ -1,18 +1,47 @@
 use image::{GenericImageView, ImageError, RgbImage};
-use std::path::Path;
+use std::error::Error;
+use std::path::{Path, PathBuf};
 
 mod textureatlas;
 
 use crate::scene::texturecoordinate::TextureCoordinate;
 use crate::util::vector::Vector;
 use std::fmt;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 pub use textureatlas::{TextureAtlas, TextureAtlasBuilder};
 
 #[derive(Debug)]
 pub enum TextureError {
-    ImageError(ImageError),
-    FileName,
+    ImageError {
+        path: PathBuf,
+        // Boxed because image errors are large
+        error: Box<ImageError>,
+    },
+    FileName(PathBuf),
+}
+
+impl Display for TextureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::ImageError { path, .. } => {
+                write!(f, "couldn't open image '{}'", path.display())
+            }
+            TextureError::FileName(path) => write!(
+                f,
+                "texture file name '{}' is not valid unicode",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl Error for TextureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TextureError::ImageError { error, .. } => Some(error.as_ref()),
+            TextureError::FileName(_) => None,
+        }
+    }
 }
 
 pub struct Texture {
@@ -28,11 +57,14 @@ impl Debug for Texture {
 
 impl Texture {
     pub fn new(filename: impl AsRef<Path>) -> Result<Self, TextureError> {
-        let image = image::open(filename).map_err(TextureError::ImageError)?;
+        let image = image::open(filename.as_ref()).map_err(|error| TextureError::ImageError {
+            path: filename.as_ref().to_path_buf(),
+            error: Box::new(error),
+        })?;
         let dimensions = image.dimensions();
 
         Ok(Self {
-            image: image.to_rgb(),
+            image: image.to_rgb8(),
             size: (dimensions.0 as usize, dimensions.1 as usize),
         })
     }
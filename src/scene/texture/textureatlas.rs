@@ -24,7 +24,7 @@ impl TextureAtlasBuilder {
             filename
                 .as_ref()
                 .to_str()
-                .ok_or(TextureError::FileName)?
+                .ok_or_else(|| TextureError::FileName(filename.as_ref().to_path_buf()))?
                 .into(),
             Texture::new(basepath.as_ref().join(filename))?,
         );
@@ -43,7 +43,7 @@ impl TextureAtlasBuilder {
         let mut textures = {
             let mut vec = Vec::with_capacity(atlassize);
             vec.resize_with(atlassize, || Texture {
-                image: DynamicImage::new_rgb8(0, 0).to_rgb(),
+                image: DynamicImage::new_rgb8(0, 0).to_rgb8(),
                 size: (0, 0),
             });
             Pin::from(vec.into_boxed_slice())
@@ -1,4 +1,5 @@
 use crate::scene::material::Material;
+use crate::scene::motion::MOTION_STEPS;
 use crate::scene::Mesh;
 use crate::scene::texturecoordinate::TextureCoordinate;
 use crate::util::vector::Vector;
@@ -48,7 +49,7 @@ impl<'t> Triangle<'t> {
     }
 
     #[inline]
-    pub fn material(&self) -> &'t Material {
+    pub fn material(&self) -> &'t Material<'t> {
         self.mesh.material
     }
 
@@ -58,6 +59,49 @@ impl<'t> Triangle<'t> {
         (self.c() - self.a()).cross(self.c() - self.b()).unit()
     }
 
+    /// The vertices at `time`, with the motion of the mesh applied.
+    /// Triangles of meshes that don't move are always at `a()`, `b()` and `c()`.
+    pub fn vertices_at(&self, time: f64) -> [Vector; 3] {
+        match &self.mesh.motion {
+            None => [self.a(), self.b(), self.c()],
+            Some(motion) => {
+                let placement = motion.at(time);
+                [
+                    placement.point(self.a()),
+                    placement.point(self.b()),
+                    placement.point(self.c()),
+                ]
+            }
+        }
+    }
+
+    pub fn normal_at(&self, time: f64) -> Vector {
+        match &self.mesh.motion {
+            None => self.normal(),
+            Some(motion) => motion.at(time).vector(self.normal()),
+        }
+    }
+
+    /// The vertices of the triangle at a number of points in time, and how far the triangle
+    /// can get from them in between. Everywhere the triangle goes while its mesh moves is
+    /// within that distance of the vertices.
+    pub fn swept_vertices(&self) -> (impl Iterator<Item = Vector> + '_, f64) {
+        let (steps, margin) = match &self.mesh.motion {
+            None => (0, 0.),
+            Some(motion) => {
+                let radius = [self.a(), self.b(), self.c()]
+                    .iter()
+                    .map(|&i| (i - motion.pivot).length())
+                    .fold(0., f64::max);
+                (MOTION_STEPS, motion.step_margin(radius))
+            }
+        };
+
+        let vertices =
+            (0..=steps).flat_map(move |i| self.vertices_at(i as f64 / steps.max(1) as f64));
+        (vertices, margin)
+    }
+
     #[inline]
     pub fn texture_a(&self) -> &TextureCoordinate {
         &self.mesh.texcoords[self.a]
@@ -83,4 +127,16 @@ impl<'t> Triangle<'t> {
         (s * (s - side1) * (s - side2) * (s - side3)).sqrt()
     }
 
+    /// Maps two uniform numbers in [0, 1) to a uniformly distributed point on the surface
+    /// of the triangle at `time`.
+    pub fn sample_point_at(&self, time: f64, (u, v): (f64, f64)) -> Vector {
+        let su = u.sqrt();
+
+        let wa = 1. - su;
+        let wb = v * su;
+
+        let [a, b, c] = self.vertices_at(time);
+        a * wa + b * wb + c * (1. - wa - wb)
+    }
+
 }
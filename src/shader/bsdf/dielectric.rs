@@ -0,0 +1,108 @@
+use crate::shader::bsdf::{reflect, Bsdf, BsdfSample};
+use crate::util::vector::Vector;
+use std::f64;
+
+/// A smooth boundary between two transparent media, like glass or water. Light is partly
+/// reflected and partly refracted (bent by Snell's law), in proportions given by the Fresnel
+/// equations. Both happen in exactly one direction, so `eval` and `pdf` are zero and only
+/// `sample` finds them.
+#[derive(Debug)]
+pub struct Dielectric {
+    /// The normal on the side of the surface that is shaded, where the ray came from.
+    normal: Vector,
+    /// The index of refraction on the side of the normal divided by the one on the other side.
+    eta: f64,
+}
+
+impl Dielectric {
+    pub fn new(normal: Vector, eta: f64) -> Self {
+        Self { normal, eta }
+    }
+}
+
+impl Bsdf for Dielectric {
+    fn eval(&self, _outgoing: Vector, _incoming: Vector) -> Vector {
+        Vector::repeated(0f64)
+    }
+
+    fn pdf(&self, _outgoing: Vector, _incoming: Vector) -> f64 {
+        0.
+    }
+
+    fn sample(&self, outgoing: Vector, pick: f64, _point: (f64, f64)) -> Option<BsdfSample> {
+        let cos_outgoing = self.normal.dot(outgoing);
+        let sin2_refracted = self.eta.powi(2) * (1. - cos_outgoing.powi(2));
+
+        // Beyond the critical angle, all light is reflected.
+        let (reflectance, cos_refracted) = if sin2_refracted >= 1. {
+            (1., 0.)
+        } else {
+            let cos_refracted = (1. - sin2_refracted).sqrt();
+            (
+                fresnel(cos_outgoing, cos_refracted, self.eta),
+                cos_refracted,
+            )
+        };
+
+        // Reflect or refract in proportion to the Fresnel reflectance,
+        // so both count in full when they are picked.
+        let direction = if pick < reflectance {
+            reflect(outgoing, self.normal)
+        } else {
+            outgoing * -self.eta + self.normal * (self.eta * cos_outgoing - cos_refracted)
+        };
+
+        Some(BsdfSample {
+            direction,
+            weight: Vector::repeated(1.),
+            pdf: f64::INFINITY,
+        })
+    }
+}
+
+/// The fraction of unpolarized light that is reflected at a boundary with the relative index
+/// of refraction `eta`, given the cosines of the angles of the light with the normal on both
+/// sides.
+fn fresnel(cos_outgoing: f64, cos_refracted: f64, eta: f64) -> f64 {
+    let parallel = (cos_outgoing - eta * cos_refracted) / (cos_outgoing + eta * cos_refracted);
+    let perpendicular = (eta * cos_outgoing - cos_refracted) / (eta * cos_outgoing + cos_refracted);
+
+    (parallel.powi(2) + perpendicular.powi(2)) / 2.
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shader::bsdf::dielectric::{fresnel, Dielectric};
+    use crate::shader::bsdf::Bsdf;
+    use crate::util::vector::Vector;
+
+    #[test]
+    fn test_fresnel() {
+        // Glass reflects 4% of the light that hits it head on.
+        assert!((fresnel(1., 1., 1. / 1.5) - 0.04).abs() < 1e-10);
+        // Nothing is reflected without a change in the index of refraction.
+        assert!(fresnel(0.6, 0.6, 1.).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_refraction() {
+        let normal = Vector::new(0., 1., 0.);
+        let outgoing = Vector::new(-1., 1., 0.).unit();
+        let refract = |eta| {
+            Dielectric::new(normal, eta)
+                .sample(outgoing, 0.99, (0., 0.))
+                .unwrap()
+                .direction
+        };
+
+        // Snell's law: the sines of the angles scale with the ratio of the indices.
+        let direction = refract(1. / 1.5);
+        assert!((direction.length() - 1.).abs() < 1e-10);
+        assert!((direction.x - 0.5f64.sqrt() / 1.5).abs() < 1e-10);
+        assert!(direction.y < 0.);
+
+        // Leaving glass at 45 degrees is beyond the critical angle, so the light is reflected.
+        let direction = refract(1.5);
+        assert!((direction - Vector::new(1., 1., 0.).unit()).length() < 1e-10);
+    }
+}
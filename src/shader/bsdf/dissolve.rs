@@ -0,0 +1,44 @@
+use crate::shader::bsdf::{Bsdf, BsdfSample};
+use crate::util::vector::Vector;
+use std::f64;
+
+/// A surface that is only partly there, like a leaf cut out of a texture with an alpha map.
+/// Light that doesn't hit the surface passes straight through it.
+pub struct Dissolve<B> {
+    /// The fraction of the light that hits `surface`.
+    opacity: f64,
+    surface: B,
+}
+
+impl<B: Bsdf> Dissolve<B> {
+    pub fn new(opacity: f64, surface: B) -> Self {
+        Self { opacity, surface }
+    }
+}
+
+impl<B: Bsdf> Bsdf for Dissolve<B> {
+    fn eval(&self, outgoing: Vector, incoming: Vector) -> Vector {
+        self.surface.eval(outgoing, incoming) * self.opacity
+    }
+
+    fn pdf(&self, outgoing: Vector, incoming: Vector) -> f64 {
+        self.surface.pdf(outgoing, incoming) * self.opacity
+    }
+
+    fn sample(&self, outgoing: Vector, pick: f64, point: (f64, f64)) -> Option<BsdfSample> {
+        if pick >= self.opacity {
+            return Some(BsdfSample {
+                direction: outgoing * -1.,
+                weight: Vector::repeated(1.),
+                pdf: f64::INFINITY,
+            });
+        }
+
+        // Both the value and the density scale with the opacity, so the weight stays the same.
+        let sample = self.surface.sample(outgoing, pick / self.opacity, point)?;
+        Some(BsdfSample {
+            pdf: sample.pdf * self.opacity,
+            ..sample
+        })
+    }
+}
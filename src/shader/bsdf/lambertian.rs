@@ -0,0 +1,91 @@
+use crate::shader::bsdf::{Bsdf, BsdfSample};
+use crate::util::vector::Vector;
+use std::f64;
+
+/// A perfectly matte surface, which scatters light equally in every direction.
+#[derive(Debug)]
+pub struct Lambertian {
+    albedo: Vector,
+    /// The normal on the side of the surface that is shaded.
+    normal: Vector,
+}
+
+impl Lambertian {
+    pub fn new(albedo: Vector, normal: Vector) -> Self {
+        Self { albedo, normal }
+    }
+}
+
+impl Bsdf for Lambertian {
+    fn eval(&self, _outgoing: Vector, incoming: Vector) -> Vector {
+        let cos = self.normal.dot(incoming);
+        if cos <= 0. {
+            return Vector::repeated(0f64);
+        }
+
+        self.albedo * (cos / f64::consts::PI)
+    }
+
+    fn pdf(&self, _outgoing: Vector, incoming: Vector) -> f64 {
+        self.normal.dot(incoming).max(0.) / f64::consts::PI
+    }
+
+    fn sample(&self, _outgoing: Vector, _pick: f64, point: (f64, f64)) -> Option<BsdfSample> {
+        let direction = Vector::point_on_diffuse_hemisphere(point).rotated(self.normal);
+        let cos = self.normal.dot(direction);
+        if cos <= 0. {
+            return None;
+        }
+
+        // Sampled proportional to the cosine, so the cosine and pi cancel out.
+        Some(BsdfSample {
+            direction,
+            weight: self.albedo,
+            pdf: cos / f64::consts::PI,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sampler::SampleStream;
+    use crate::shader::bsdf::lambertian::Lambertian;
+    use crate::shader::bsdf::Bsdf;
+    use crate::util::rng::reseed;
+    use crate::util::vector::Vector;
+
+    #[test]
+    fn test_lambertian() {
+        let albedo = Vector::new(0.8, 0.5, 0.2);
+        let normal = Vector::new(1., 2., -1.).unit();
+        let bsdf = Lambertian::new(albedo, normal);
+        let outgoing = normal;
+        reseed(7);
+        let mut samples = SampleStream::random();
+
+        let mut reflected = Vector::repeated(0.);
+        let count = 20000;
+        for _ in 0..count {
+            let sample = bsdf
+                .sample(outgoing, samples.next_1d(), samples.next_2d())
+                .unwrap();
+
+            // The sample is consistent with eval and pdf.
+            let pdf = bsdf.pdf(outgoing, sample.direction);
+            let value = bsdf.eval(outgoing, sample.direction);
+            assert!((sample.pdf - pdf).abs() < 1e-9);
+            assert!((sample.weight * pdf - value).length() < 1e-9);
+
+            // Integrating eval over the sphere (with a density of 1 / 4pi) gives the albedo.
+            let direction = Vector::point_on_sphere(samples.next_2d());
+            reflected += bsdf.eval(outgoing, direction) * (4. * std::f64::consts::PI);
+        }
+
+        assert!((reflected / count as f64 - albedo).length() < 0.02);
+        assert_eq!(
+            bsdf.eval(outgoing, normal * -1.),
+            Vector::repeated(0.),
+            "no light from behind"
+        );
+    }
+}
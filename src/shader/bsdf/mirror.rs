@@ -0,0 +1,70 @@
+use crate::shader::bsdf::{reflect, Bsdf, BsdfSample};
+use crate::util::vector::Vector;
+use std::f64;
+
+/// A perfect mirror, which reflects light in exactly one direction. That direction can't be
+/// found by chance, so `eval` and `pdf` are zero and only `sample` finds the reflection.
+#[derive(Debug)]
+pub struct Mirror {
+    reflectance: Vector,
+    /// The normal on the side of the surface that is shaded.
+    normal: Vector,
+}
+
+impl Mirror {
+    pub fn new(reflectance: Vector, normal: Vector) -> Self {
+        Self {
+            reflectance,
+            normal,
+        }
+    }
+}
+
+impl Bsdf for Mirror {
+    fn eval(&self, _outgoing: Vector, _incoming: Vector) -> Vector {
+        Vector::repeated(0f64)
+    }
+
+    fn pdf(&self, _outgoing: Vector, _incoming: Vector) -> f64 {
+        0.
+    }
+
+    fn sample(&self, outgoing: Vector, _pick: f64, _point: (f64, f64)) -> Option<BsdfSample> {
+        let direction = reflect(outgoing, self.normal);
+        if self.normal.dot(direction) <= 0. {
+            return None;
+        }
+
+        Some(BsdfSample {
+            direction,
+            weight: self.reflectance,
+            pdf: f64::INFINITY,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shader::bsdf::mirror::Mirror;
+    use crate::shader::bsdf::Bsdf;
+    use crate::util::vector::Vector;
+
+    #[test]
+    fn test_mirror() {
+        let normal = Vector::new(0., 1., 0.);
+        let outgoing = Vector::new(1., 2., 0.).unit();
+        let reflectance = Vector::new(0.9, 0.5, 0.1);
+        let bsdf = Mirror::new(reflectance, normal);
+
+        // Every sample is the reflection, which can't be found by chance.
+        let sample = bsdf.sample(outgoing, 0.3, (0.6, 0.2)).unwrap();
+        assert!((sample.direction - Vector::new(-1., 2., 0.).unit()).length() < 1e-10);
+        assert!(sample.pdf.is_infinite());
+        assert_eq!(sample.weight, reflectance);
+        assert_eq!(bsdf.pdf(outgoing, sample.direction), 0.);
+        assert_eq!(bsdf.eval(outgoing, sample.direction), Vector::repeated(0.));
+
+        // Light from below the surface isn't reflected.
+        assert!(bsdf.sample(normal * -1., 0.3, (0.6, 0.2)).is_none());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::shader::bsdf::{Bsdf, BsdfSample};
+use crate::util::vector::Vector;
+
+/// The sum of two bsdfs. `sample` picks one of them, in proportion to their weights,
+/// which are usually how much light each of them reflects.
+pub struct Mix<A, B> {
+    /// The bsdfs with the probability with which `sample` picks them.
+    first: (f64, A),
+    second: (f64, B),
+}
+
+impl<A: Bsdf, B: Bsdf> Mix<A, B> {
+    pub fn new(first: (f64, A), second: (f64, B)) -> Self {
+        let total = first.0 + second.0;
+        // Without any weight, neither is picked and the light is absorbed.
+        let scale = if total > 0. { 1. / total } else { 0. };
+
+        Self {
+            first: (first.0 * scale, first.1),
+            second: (second.0 * scale, second.1),
+        }
+    }
+}
+
+impl<A: Bsdf, B: Bsdf> Bsdf for Mix<A, B> {
+    fn eval(&self, outgoing: Vector, incoming: Vector) -> Vector {
+        self.first.1.eval(outgoing, incoming) + self.second.1.eval(outgoing, incoming)
+    }
+
+    fn pdf(&self, outgoing: Vector, incoming: Vector) -> f64 {
+        let (first, second) = (&self.first, &self.second);
+        let mut pdf = 0.;
+        if first.0 > 0. {
+            pdf += first.0 * first.1.pdf(outgoing, incoming);
+        }
+        if second.0 > 0. {
+            pdf += second.0 * second.1.pdf(outgoing, incoming);
+        }
+        pdf
+    }
+
+    fn sample(&self, outgoing: Vector, pick: f64, point: (f64, f64)) -> Option<BsdfSample> {
+        // Rescale `pick` to [0, 1) again, so the picked bsdf can use it to choose as well.
+        let rescale = |pick: f64, probability: f64| (pick / probability).min(1. - f64::EPSILON);
+
+        let (probability, sample) = if pick < self.first.0 {
+            let pick = rescale(pick, self.first.0);
+            (self.first.0, self.first.1.sample(outgoing, pick, point)?)
+        } else if self.second.0 > 0. {
+            let pick = rescale(pick - self.first.0, self.second.0);
+            (self.second.0, self.second.1.sample(outgoing, pick, point)?)
+        } else {
+            return None;
+        };
+
+        if sample.pdf.is_infinite() {
+            // The other bsdf can't find a single direction by chance.
+            return Some(BsdfSample {
+                weight: sample.weight / probability,
+                ..sample
+            });
+        }
+
+        let pdf = self.pdf(outgoing, sample.direction);
+        if pdf <= 0. {
+            return None;
+        }
+
+        Some(BsdfSample {
+            direction: sample.direction,
+            weight: self.eval(outgoing, sample.direction) / pdf,
+            pdf,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sampler::SampleStream;
+    use crate::shader::bsdf::lambertian::Lambertian;
+    use crate::shader::bsdf::mirror::Mirror;
+    use crate::shader::bsdf::mix::Mix;
+    use crate::shader::bsdf::phong::Phong;
+    use crate::shader::bsdf::Bsdf;
+    use crate::util::rng::reseed;
+    use crate::util::vector::Vector;
+
+    #[test]
+    fn test_mix() {
+        let normal = Vector::new(0., 1., 0.);
+        let outgoing = Vector::new(1., 1., 0.).unit();
+        let bsdf = Mix::new(
+            (0.3, Lambertian::new(Vector::repeated(0.3), normal)),
+            (0.5, Phong::new(Vector::repeated(0.5), 20., normal)),
+        );
+        reseed(7);
+        let mut samples = SampleStream::random();
+
+        for _ in 0..2000 {
+            let sample =
+                if let Some(sample) = bsdf.sample(outgoing, samples.next_1d(), samples.next_2d()) {
+                    sample
+                } else {
+                    continue;
+                };
+
+            // The sample is consistent with eval and pdf, whichever of the two picked it.
+            let pdf = bsdf.pdf(outgoing, sample.direction);
+            let value = bsdf.eval(outgoing, sample.direction);
+            assert!((sample.pdf - pdf).abs() < 1e-9 * pdf);
+            assert!((sample.weight * pdf - value).length() < 1e-9 * pdf);
+        }
+    }
+
+    #[test]
+    fn test_mix_with_mirror() {
+        let normal = Vector::new(0., 1., 0.);
+        let outgoing = Vector::new(1., 1., 0.).unit();
+        let bsdf = Mix::new(
+            (0.3, Lambertian::new(Vector::repeated(0.3), normal)),
+            (0.5, Mirror::new(Vector::repeated(0.5), normal)),
+        );
+        reseed(7);
+        let mut samples = SampleStream::random();
+
+        let mut reflected = Vector::repeated(0.);
+        let mut mirrored = 0;
+        let count = 20000;
+        for _ in 0..count {
+            let sample = bsdf
+                .sample(outgoing, samples.next_1d(), samples.next_2d())
+                .unwrap();
+
+            if sample.pdf.is_infinite() {
+                // The mirror is picked 5 / 8 of the time, so its reflectance counts 8 / 5 times.
+                mirrored += 1;
+                assert!((sample.direction - Vector::new(-1., 1., 0.).unit()).length() < 1e-10);
+                assert!((sample.weight - Vector::repeated(0.8)).length() < 1e-10);
+            } else {
+                // The mirror adds nothing to eval and pdf outside its one direction.
+                let pdf = bsdf.pdf(outgoing, sample.direction);
+                let value = bsdf.eval(outgoing, sample.direction);
+                assert!((sample.pdf - pdf).abs() < 1e-9 * pdf);
+                assert!((sample.weight * pdf - value).length() < 1e-9 * pdf);
+            }
+
+            reflected += sample.weight;
+        }
+
+        // Together they reflect what both reflect.
+        assert!((mirrored as f64 / count as f64 - 0.625).abs() < 0.02);
+        assert!((reflected / count as f64 - Vector::repeated(0.8)).length() < 0.02);
+    }
+}
@@ -0,0 +1,237 @@
+//! Bidirectional scattering distribution functions (BSDFs) describe how a surface scatters the
+//! light that arrives at it. Shaders build one for every hit with `bsdf_at`, from the material
+//! of the triangle that was hit, so they don't have to know about the material models.
+//!
+//! All directions are unit vectors pointing away from the surface. `outgoing` points to where
+//! the light goes (back along the path, towards the camera), `incoming` to where it comes from.
+use crate::datastructure::intersection::Intersection;
+use crate::scene::material::Material;
+use crate::scene::texture::Texture;
+use crate::shader::bsdf::dielectric::Dielectric;
+use crate::shader::bsdf::dissolve::Dissolve;
+use crate::shader::bsdf::lambertian::Lambertian;
+use crate::shader::bsdf::mirror::Mirror;
+use crate::shader::bsdf::mix::Mix;
+use crate::shader::bsdf::phong::Phong;
+use crate::shader::shaders::map_uv;
+use crate::util::vector::Vector;
+
+pub mod dielectric;
+pub mod dissolve;
+pub mod lambertian;
+pub mod mirror;
+pub mod mix;
+pub mod phong;
+
+/// Materials with at least this shininess (`Ns`, which goes up to 1000 in MTL files)
+/// are perfect mirrors.
+const MIRROR_SHININESS: f64 = 1000.;
+
+/// A direction sampled by `Bsdf::sample`.
+pub struct BsdfSample {
+    /// The direction the light comes from.
+    pub direction: Vector,
+    /// `eval` in `direction` divided by `pdf`, the factor to multiply the light
+    /// found in `direction` with.
+    pub weight: Vector,
+    /// The probability density (per unit solid angle) with which `direction` was picked.
+    pub pdf: f64,
+}
+
+pub trait Bsdf {
+    /// The fraction of the light arriving from `incoming` that leaves towards `outgoing`, per
+    /// unit solid angle, times the cosine between `incoming` and the normal.
+    fn eval(&self, outgoing: Vector, incoming: Vector) -> Vector;
+
+    /// The probability density (per unit solid angle) with which `sample` picks `incoming`.
+    fn pdf(&self, outgoing: Vector, incoming: Vector) -> f64;
+
+    /// Picks a direction for the light to come from, with `pick` (uniform in [0, 1)) to choose
+    /// between the parts of a bsdf and `point` (uniform in the unit square) for the direction.
+    /// Every bsdf takes both, so every bounce of a path takes the same dimensions.
+    /// Returns None when the surface absorbs the light.
+    fn sample(&self, outgoing: Vector, pick: f64, point: (f64, f64)) -> Option<BsdfSample>;
+}
+
+/// The bsdf of the material of the triangle at `intersection`, on the side of the triangle
+/// that the ray came from.
+///
+/// Materials with an illumination model that turns on glass or refraction are glass with the
+/// index of refraction `optical_density`. Other materials are diffuse. If their illumination
+/// model turns on reflections, they also reflect the fraction `specular` of the light: as a
+/// mirror, or as a glossy reflection that gets sharper with the shininess. Only the rest of the
+/// light is diffuse. Any other material with a dissolve below 1 lets that fraction of the light
+/// pass through, and opaque materials (with a dissolve of 1) let nothing through.
+pub fn bsdf_at(intersection: &Intersection) -> Dissolve<SurfaceBsdf> {
+    let material = intersection.triangle.material();
+    let surface = surface_bsdf(material, intersection);
+
+    // The dissolve of glass is how transparent it is in renderers without refraction,
+    // refracting the light takes its place.
+    if let SurfaceBsdf::Glass(_) = surface {
+        return Dissolve::new(1., surface);
+    }
+
+    // Dissolve maps are grayscale.
+    let opacity = material.dissolve * texture_at(material.dissolve_texture, intersection).x;
+    Dissolve::new(opacity.clamp(0., 1.), surface)
+}
+
+/// The bsdf of a material where it isn't dissolved. An enum rather than a `Box<dyn Bsdf>`,
+/// so building one for every hit doesn't allocate.
+pub enum SurfaceBsdf {
+    Diffuse(Lambertian),
+    Glossy(Mix<Lambertian, Phong>),
+    Mirror(Mix<Lambertian, Mirror>),
+    Glass(Dielectric),
+}
+
+impl Bsdf for SurfaceBsdf {
+    fn eval(&self, outgoing: Vector, incoming: Vector) -> Vector {
+        match self {
+            SurfaceBsdf::Diffuse(bsdf) => bsdf.eval(outgoing, incoming),
+            SurfaceBsdf::Glossy(bsdf) => bsdf.eval(outgoing, incoming),
+            SurfaceBsdf::Mirror(bsdf) => bsdf.eval(outgoing, incoming),
+            SurfaceBsdf::Glass(bsdf) => bsdf.eval(outgoing, incoming),
+        }
+    }
+
+    fn pdf(&self, outgoing: Vector, incoming: Vector) -> f64 {
+        match self {
+            SurfaceBsdf::Diffuse(bsdf) => bsdf.pdf(outgoing, incoming),
+            SurfaceBsdf::Glossy(bsdf) => bsdf.pdf(outgoing, incoming),
+            SurfaceBsdf::Mirror(bsdf) => bsdf.pdf(outgoing, incoming),
+            SurfaceBsdf::Glass(bsdf) => bsdf.pdf(outgoing, incoming),
+        }
+    }
+
+    fn sample(&self, outgoing: Vector, pick: f64, point: (f64, f64)) -> Option<BsdfSample> {
+        match self {
+            SurfaceBsdf::Diffuse(bsdf) => bsdf.sample(outgoing, pick, point),
+            SurfaceBsdf::Glossy(bsdf) => bsdf.sample(outgoing, pick, point),
+            SurfaceBsdf::Mirror(bsdf) => bsdf.sample(outgoing, pick, point),
+            SurfaceBsdf::Glass(bsdf) => bsdf.sample(outgoing, pick, point),
+        }
+    }
+}
+
+/// The bsdf of `material` where it isn't dissolved.
+fn surface_bsdf(material: &Material, intersection: &Intersection) -> SurfaceBsdf {
+    // Normals point out of closed meshes, so rays against the normal enter them.
+    let entering = intersection.normal().dot(intersection.ray.direction) < 0.;
+    let normal = if entering {
+        intersection.normal()
+    } else {
+        intersection.normal() * -1.
+    };
+
+    if refracts(material) {
+        let eta = if entering {
+            1. / material.optical_density
+        } else {
+            material.optical_density
+        };
+        return SurfaceBsdf::Glass(Dielectric::new(normal, eta));
+    }
+
+    let diffuse = material.diffuse * texture_at(material.diffuse_texture, intersection);
+    if !reflects(material) {
+        return SurfaceBsdf::Diffuse(Lambertian::new(diffuse, normal));
+    }
+
+    let specular = material.specular * texture_at(material.specular_texture, intersection);
+    let diffuse = diffuse * (Vector::repeated(1.) - specular);
+    let diffuse = (diffuse.max_item(), Lambertian::new(diffuse, normal));
+
+    if material.shininess >= MIRROR_SHININESS {
+        let mirror = Mirror::new(specular, normal);
+        SurfaceBsdf::Mirror(Mix::new(diffuse, (specular.max_item(), mirror)))
+    } else {
+        let phong = Phong::new(specular, material.shininess, normal);
+        SurfaceBsdf::Glossy(Mix::new(diffuse, (specular.max_item(), phong)))
+    }
+}
+
+/// Whether the illumination model of `material` makes it glass, following the table at
+/// http://paulbourke.net/dataformats/mtl/. Glass reflects and refracts with the Fresnel
+/// equations, whichever of these models a material has.
+fn refracts(material: &Material) -> bool {
+    matches!(
+        material.illumination_model,
+        // 4: glass on, reflection: ray trace on.
+        // 6: refraction on, reflection: Fresnel off and ray trace on.
+        // 7: refraction on, reflection: Fresnel on and ray trace on.
+        // 9: glass on, reflection: ray trace off.
+        Some(4) | Some(6) | Some(7) | Some(9)
+    )
+}
+
+/// Whether the illumination model of `material` turns on ray traced reflections, following
+/// the table at http://paulbourke.net/dataformats/mtl/. Models 8 and 9 turn ray tracing off
+/// (they only reflect a reflection map), and the models below 3, like the highlights of
+/// model 2, only describe how point lights are shaded.
+fn reflects(material: &Material) -> bool {
+    matches!(
+        material.illumination_model,
+        // 3: reflection on and ray trace on.
+        // 4: glass on, reflection: ray trace on.
+        // 5: reflection: Fresnel on and ray trace on.
+        // 6 and 7: refraction on, reflection: ray trace on.
+        Some(3) | Some(4) | Some(5) | Some(6) | Some(7)
+    )
+}
+
+/// The color of `texture` at `intersection`, or white for materials without the texture.
+fn texture_at(texture: Option<&Texture>, intersection: &Intersection) -> Vector {
+    if let Some(texture) = texture {
+        texture.at(map_uv(intersection))
+    } else {
+        Vector::new(1., 1., 1.)
+    }
+}
+
+/// `direction` mirrored in `normal`. Both point away from the surface.
+fn reflect(direction: Vector, normal: Vector) -> Vector {
+    2. * normal.dot(direction) * normal - direction
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datastructure::intersection::Intersection;
+    use crate::scene::SceneBuilder;
+    use crate::shader::bsdf::{bsdf_at, Bsdf};
+    use crate::util::ray::Ray;
+
+    #[test]
+    fn test_hard_box_glass() {
+        let scene = SceneBuilder::new()
+            .build_from_tobj(tobj::load_obj("scenes/hard-box.obj".as_ref()).unwrap())
+            .unwrap();
+        // Glass with illum 9, Ni 1.45 and a dissolve of 0.07.
+        let triangle = scene
+            .triangles()
+            .find(|i| i.material().name == "Glass")
+            .unwrap();
+
+        // A ray that hits the middle of the triangle at 45 degrees.
+        let normal = triangle.normal();
+        let center = (triangle.a() + triangle.b() + triangle.c()) / 3.;
+        let direction = ((triangle.b() - triangle.a()).unit() - normal).unit();
+        let ray = Ray::new(center - direction, direction);
+        let intersection = Intersection {
+            ray: &ray,
+            uv: (1. / 3., 1. / 3.),
+            t: 1.,
+            triangle,
+        };
+
+        // Most of the light enters the glass, bent towards the normal by Snell's law,
+        // instead of passing straight through.
+        let sample = bsdf_at(&intersection)
+            .sample(direction * -1., 0.99, (0.5, 0.5))
+            .unwrap();
+        let sine = sample.direction.cross(normal).length();
+        assert!(sample.direction.dot(normal) < 0.);
+        assert!((sine - 0.5f64.sqrt() / 1.45).abs() < 1e-6, "{}", sine);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::shader::bsdf::{reflect, Bsdf, BsdfSample};
+use crate::util::vector::Vector;
+use std::f64;
+
+/// A glossy reflection: the normalized (energy conserving) Phong lobe around the mirror
+/// direction. The larger the exponent, the narrower the lobe and the sharper the reflection.
+#[derive(Debug)]
+pub struct Phong {
+    reflectance: Vector,
+    exponent: f64,
+    /// The normal on the side of the surface that is shaded.
+    normal: Vector,
+}
+
+impl Phong {
+    pub fn new(reflectance: Vector, exponent: f64, normal: Vector) -> Self {
+        Self {
+            reflectance,
+            exponent,
+            normal,
+        }
+    }
+
+    /// The cosine of the angle between `incoming` and the mirror direction of `outgoing`,
+    /// raised to the exponent.
+    fn lobe(&self, outgoing: Vector, incoming: Vector) -> f64 {
+        let cos = reflect(outgoing, self.normal).dot(incoming);
+        if cos <= 0. {
+            0.
+        } else {
+            cos.powf(self.exponent)
+        }
+    }
+}
+
+impl Bsdf for Phong {
+    fn eval(&self, outgoing: Vector, incoming: Vector) -> Vector {
+        let cos = self.normal.dot(incoming);
+        if cos <= 0. {
+            return Vector::repeated(0f64);
+        }
+
+        let normalization = (self.exponent + 2.) / (2. * f64::consts::PI);
+        self.reflectance * (normalization * self.lobe(outgoing, incoming) * cos)
+    }
+
+    fn pdf(&self, outgoing: Vector, incoming: Vector) -> f64 {
+        if self.normal.dot(incoming) <= 0. {
+            return 0.;
+        }
+
+        (self.exponent + 1.) / (2. * f64::consts::PI) * self.lobe(outgoing, incoming)
+    }
+
+    fn sample(&self, outgoing: Vector, _pick: f64, (u, v): (f64, f64)) -> Option<BsdfSample> {
+        // A direction around +y with a density proportional to the lobe,
+        // turned to be around the mirror direction.
+        let cos_lobe = u.powf(1. / (self.exponent + 1.));
+        let sin_lobe = (1. - cos_lobe * cos_lobe).max(0.).sqrt();
+        let phi = 2. * f64::consts::PI * v;
+        let direction = Vector::new(sin_lobe * phi.cos(), cos_lobe, sin_lobe * phi.sin())
+            .rotated(reflect(outgoing, self.normal));
+
+        // Directions of the lobe that point into the surface are absorbed.
+        let cos = self.normal.dot(direction);
+        if cos <= 0. {
+            return None;
+        }
+
+        // The lobe cancels out against the density.
+        Some(BsdfSample {
+            direction,
+            weight: self.reflectance * ((self.exponent + 2.) / (self.exponent + 1.) * cos),
+            pdf: self.pdf(outgoing, direction),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sampler::SampleStream;
+    use crate::shader::bsdf::phong::Phong;
+    use crate::shader::bsdf::Bsdf;
+    use crate::util::rng::reseed;
+    use crate::util::vector::Vector;
+
+    #[test]
+    fn test_phong() {
+        let normal = Vector::new(0., 1., 0.);
+        let outgoing = Vector::new(1., 1., 0.).unit();
+        let bsdf = Phong::new(Vector::repeated(0.5), 20., normal);
+        reseed(7);
+        let mut samples = SampleStream::random();
+
+        let mut reflected = Vector::repeated(0.);
+        let count = 20000;
+        for _ in 0..count {
+            let sample =
+                if let Some(sample) = bsdf.sample(outgoing, samples.next_1d(), samples.next_2d()) {
+                    sample
+                } else {
+                    continue;
+                };
+
+            // The sample is consistent with eval and pdf.
+            let pdf = bsdf.pdf(outgoing, sample.direction);
+            let value = bsdf.eval(outgoing, sample.direction);
+            assert!((sample.pdf - pdf).abs() < 1e-9 * pdf);
+            assert!((sample.weight * pdf - value).length() < 1e-9 * pdf);
+
+            reflected += sample.weight;
+        }
+
+        // Some of the lobe points into the surface at this angle, but the lobe
+        // never reflects more than the reflectance.
+        let reflected = reflected / count as f64;
+        assert!(reflected.x > 0.3 && reflected.x < 0.5, "{:?}", reflected);
+    }
+}
@@ -0,0 +1,126 @@
+//! Next event estimation: explicitly sampling light sources from a shaded point, instead of
+//! waiting for a bounce to hit one by chance. The light samples are combined with the
+//! bounces of a shader through multiple importance sampling (MIS), so both strategies
+//! together still give the same expected value as bouncing alone.
+use crate::datastructure::intersection::Intersection;
+use crate::datastructure::DataStructure;
+use crate::sampler::SampleStream;
+use crate::shader::shaders::emittance;
+use crate::util::ray::Ray;
+use crate::util::statistics::{count_ray, RayType};
+use crate::util::vector::Vector;
+use std::ptr;
+
+/// A direction towards a randomly chosen point on a light source.
+/// Only returned by `sample_light` when that point is visible.
+pub struct LightSample {
+    /// Unit vector pointing from the shaded point to the light.
+    pub direction: Vector,
+    /// Distance from the shaded point to the light.
+    pub distance: f64,
+    /// Probability density (per unit solid angle) with which `direction` was picked.
+    pub pdf: f64,
+    /// The light emitted towards the shaded point.
+    pub emittance: Vector,
+}
+
+/// Picks a point on one of the light sources of the scene and casts a shadow ray to it
+/// from `hit_pos`. Returns None if the scene has no lights or the point is occluded.
+pub fn sample_light(
+    intersection: &Intersection,
+    hit_pos: Vector,
+    datastructure: &dyn DataStructure,
+    samples: &mut SampleStream,
+) -> Option<LightSample> {
+    // Always take the same dimensions, also when there are no lights.
+    let pick = samples.next_1d();
+    let point = samples.next_2d();
+
+    let lightsourcemanager = intersection.triangle.mesh.lightsourcemanager();
+    let light = lightsourcemanager.sample_source(pick)?;
+
+    let time = intersection.ray.time;
+    let to_light = light.sample_point_at(time, point) - hit_pos;
+    let distance = to_light.length();
+    if distance <= 0. {
+        return None;
+    }
+
+    let direction = to_light / distance;
+    let cos_light = light.normal_at(time).dot(direction).abs();
+    if cos_light <= 0. {
+        return None;
+    }
+
+    let shadow_ray = Ray::new(hit_pos, direction).with_time(time);
+    count_ray(RayType::Shadow);
+    let shadow_hit = datastructure.intersects(&shadow_ray)?;
+    if !ptr::eq(shadow_hit.triangle, light) {
+        return None;
+    }
+
+    let pdf = lightsourcemanager.pdf(light) * distance.powi(2) / (light.area() * cos_light);
+
+    Some(LightSample {
+        direction,
+        distance,
+        pdf,
+        emittance: emittance(&shadow_hit),
+    })
+}
+
+/// The probability density (per unit solid angle) with which `sample_light` would have picked
+/// the direction of `intersection.ray`, seen from the origin of that ray.
+/// Zero when the triangle that was hit isn't a light source.
+pub fn light_pdf(intersection: &Intersection) -> f64 {
+    let triangle = intersection.triangle;
+    let pick = triangle.mesh.lightsourcemanager().pdf(triangle);
+    if pick <= 0. {
+        return 0.;
+    }
+
+    let to_light = intersection.hit_pos() - intersection.ray.origin;
+    let cos_light = intersection.normal().dot(to_light.unit()).abs();
+    if cos_light <= 0. {
+        return 0.;
+    }
+
+    pick * to_light.length2() / (triangle.area() * cos_light)
+}
+
+/// The power heuristic (with β = 2) for weighing a sample taken with density `pdf`,
+/// where `other_pdf` is the density the other strategy has for the same sample.
+pub fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    if pdf.is_infinite() {
+        return 1.;
+    }
+
+    let a = pdf * pdf;
+    let b = other_pdf * other_pdf;
+
+    if a + b <= 0. {
+        0.
+    } else {
+        a / (a + b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shader::lightsampling::power_heuristic;
+
+    #[test]
+    fn test_power_heuristic_sums_to_one() {
+        for (a, b) in &[(0.5, 0.5), (1., 3.), (0.01, 20.), (4., 0.)] {
+            let sum = power_heuristic(*a, *b) + power_heuristic(*b, *a);
+            assert!((sum - 1.).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_power_heuristic_prefers_larger_pdf() {
+        assert!(power_heuristic(2., 1.) > 0.5);
+        assert_eq!(power_heuristic(1., 0.), 1.);
+        assert_eq!(power_heuristic(0., 1.), 0.);
+    }
+}
@@ -1,54 +1,147 @@
+use crate::datastructure::intersection::Intersection;
 use crate::datastructure::DataStructure;
-use crate::shader::shaders::{diffuse, emittance};
+use crate::sampler::SampleStream;
+use crate::shader::bsdf::{bsdf_at, Bsdf};
+use crate::shader::lightsampling::{light_pdf, power_heuristic, sample_light};
+use crate::shader::roulette::RussianRoulette;
+use crate::shader::shaders::emittance;
 use crate::shader::Shader;
 use crate::util::ray::Ray;
+use crate::util::statistics::{count_path, count_ray, PathEnd, RayType};
 use crate::util::vector::Vector;
 
 #[derive(Debug)]
-pub struct McShader;
+pub struct McShader {
+    /// Sample light sources directly at every bounce (next event estimation).
+    light_sampling: bool,
+    roulette: RussianRoulette,
+}
 
 impl McShader {
-    pub fn shade_internal<'a>(
+    pub fn new(light_sampling: bool, roulette: RussianRoulette) -> Self {
+        Self {
+            light_sampling,
+            roulette,
+        }
+    }
+
+    /// Follows the path of `ray` bounce by bounce, adding up the light that reaches the
+    /// camera along it. The light found at every bounce is weighted by the throughput of the
+    /// path so far: the fraction of it that is scattered all the way back to the camera.
+    pub fn trace_path<'a>(
         &self,
         ray: &Ray,
-        depth: usize,
         datastructure: &'a (dyn DataStructure + 'a),
+        samples: &mut SampleStream,
     ) -> Vector {
-        //        let pointlight = Vector::new(0f64, 0.2f64, 1f64);
-        //        let brightness = Vector::repeated(0f64);
-
-        let intersection = if let Some(intersection) = datastructure.intersects(&ray) {
-            intersection
-        } else {
-            return Vector::repeated(0f64);
-        };
-        //
-        let hit_pos = intersection.hit_pos();
-        //
-        //        let part_amb = ambient(&intersection.face, self.scene) * Vector::repeated(0.1);
-        let part_emi = emittance(&intersection);
-        //        let part_diff = diffuse(&intersection.face, self.scene, hit_pos, pointlight) * brightness;
-        //        let part_spec = specular(&intersection.face, self.scene, hit_pos, pointlight, intersection.ray.origin) * brightness;
-        //
-        //
-        //        let direct = part_amb + part_emi + part_diff + part_spec;
-
-        let indirect = if depth > 0 {
-            let bounce_direction =
-                Vector::point_on_hemisphere().rotated(intersection.triangle.normal());
-            let bounce_ray = Ray::new(hit_pos, bounce_direction);
-            let indirect_light = self.shade_internal(&bounce_ray, depth - 1, datastructure);
-            indirect_light * diffuse(&intersection, hit_pos, hit_pos + bounce_direction)
-        } else {
-            Vector::repeated(0f64)
-        };
-
-        indirect * 2. + part_emi
+        let mut ray = ray.clone();
+        let mut radiance = Vector::repeated(0f64);
+        let mut throughput = Vector::repeated(1f64);
+        // The probability density with which the direction of `ray` was sampled at the
+        // previous bounce. It is None for camera rays, whose emission is always counted
+        // in full.
+        let mut bsdf_pdf = None;
+
+        for bounce in 0.. {
+            let intersection = if let Some(intersection) = datastructure.intersects(&ray) {
+                intersection
+            } else {
+                count_path(bounce, PathEnd::Escaped);
+                break;
+            };
+            let hit_pos = intersection.hit_pos();
+
+            radiance += throughput
+                * emittance(&intersection)
+                * self.emittance_weight(&intersection, bsdf_pdf);
+
+            if !self.roulette.can_bounce(bounce) {
+                count_path(bounce, PathEnd::MaxDepth);
+                break;
+            }
+
+            let bsdf = bsdf_at(&intersection);
+            let outgoing = ray.direction * -1.;
+
+            // Sampled before bouncing, so every bounce of a path takes the same dimensions.
+            if self.light_sampling {
+                radiance += throughput
+                    * self.direct_light(
+                        &intersection,
+                        &bsdf,
+                        hit_pos,
+                        outgoing,
+                        datastructure,
+                        samples,
+                    );
+            }
+
+            let survival =
+                if let Some(survival) = self.roulette.survive(bounce, throughput, samples) {
+                    survival
+                } else {
+                    count_path(bounce, PathEnd::Roulette);
+                    break;
+                };
+
+            let pick = samples.next_1d();
+            let point = samples.next_2d();
+            let sample = if let Some(sample) = bsdf.sample(outgoing, pick, point) {
+                sample
+            } else {
+                count_path(bounce, PathEnd::Absorbed);
+                break;
+            };
+
+            throughput = throughput * sample.weight / survival;
+            bsdf_pdf = Some(sample.pdf);
+            ray = Ray::new(hit_pos, sample.direction).with_time(ray.time);
+            count_ray(RayType::Bounce);
+        }
+
+        radiance
+    }
+
+    /// The MIS weight of light that a bounce found by chance. Without light sampling,
+    /// bounces are the only way to find light, so it counts in full.
+    fn emittance_weight(&self, intersection: &Intersection, bsdf_pdf: Option<f64>) -> f64 {
+        match bsdf_pdf {
+            Some(pdf) if self.light_sampling => power_heuristic(pdf, light_pdf(intersection)),
+            _ => 1.,
+        }
+    }
+
+    /// Light arriving at `hit_pos` directly from a sampled point on a light source,
+    /// scattered towards `outgoing`.
+    fn direct_light(
+        &self,
+        intersection: &Intersection,
+        bsdf: &dyn Bsdf,
+        hit_pos: Vector,
+        outgoing: Vector,
+        datastructure: &dyn DataStructure,
+        samples: &mut SampleStream,
+    ) -> Vector {
+        let sample =
+            if let Some(sample) = sample_light(intersection, hit_pos, datastructure, samples) {
+                sample
+            } else {
+                return Vector::repeated(0f64);
+            };
+
+        let weight = power_heuristic(sample.pdf, bsdf.pdf(outgoing, sample.direction));
+
+        sample.emittance * bsdf.eval(outgoing, sample.direction) * (weight / sample.pdf)
     }
 }
 
 impl Shader for McShader {
-    fn shade<'s>(&self, ray: &Ray, datastructure: &'s (dyn DataStructure + 's)) -> Vector {
-        self.shade_internal(ray, 4, datastructure)
+    fn shade<'s>(
+        &self,
+        ray: &Ray,
+        datastructure: &'s (dyn DataStructure + 's),
+        samples: &mut SampleStream,
+    ) -> Vector {
+        self.trace_path(ray, datastructure, samples)
     }
 }
@@ -1,10 +1,14 @@
 use crate::datastructure::DataStructure;
+use crate::sampler::SampleStream;
 use crate::util::ray::Ray;
 use crate::util::vector::Vector;
-use serde::export::fmt::Debug;
+use std::fmt::Debug;
 
+pub mod bsdf;
+pub mod lightsampling;
 pub mod mcshader;
 pub mod mtlshader;
+pub mod roulette;
 pub mod shaders;
 pub mod vmcshader;
 
@@ -12,6 +16,85 @@ pub mod vmcshader;
 /// and asks the `datastructure` where it lands. Based on the `Intersection` struct
 /// it gets back, it can give a color to a pixel. A shader can query the `datastructure`
 /// multiple times to achieve such things as reflection, refraction, and other effects.
+/// Every random decision takes its numbers from `samples`.
 pub trait Shader: Send + Sync + Debug {
-    fn shade<'s>(&self, ray: &Ray, datastructure: &'s dyn DataStructure) -> Vector;
+    fn shade(
+        &self,
+        ray: &Ray,
+        datastructure: &dyn DataStructure,
+        samples: &mut SampleStream,
+    ) -> Vector;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datastructure::bvh::KDTreeDataStructure;
+    use crate::sampler::SampleStream;
+    use crate::scene::SceneBuilder;
+    use crate::shader::mcshader::McShader;
+    use crate::shader::roulette::RussianRoulette;
+    use crate::shader::vmcshader::VMcShader;
+    use crate::shader::Shader;
+    use crate::util::ray::Ray;
+    use crate::util::rng::reseed;
+    use crate::util::vector::Vector;
+    use std::io::BufReader;
+
+    /// A closed box that emits 0.5 and diffusely reflects half of the light everywhere.
+    const FURNACE: &str = "
+        mtllib furnace.mtl
+        v -1 -1 -1
+        v 1 -1 -1
+        v 1 1 -1
+        v -1 1 -1
+        v -1 -1 1
+        v 1 -1 1
+        v 1 1 1
+        v -1 1 1
+        usemtl furnace
+        f 1 2 3 4
+        f 5 8 7 6
+        f 1 5 6 2
+        f 4 3 7 8
+        f 1 4 8 5
+        f 2 6 7 3
+    ";
+
+    const FURNACE_MTL: &str = "
+        newmtl furnace
+        Kd 0.5 0.5 0.5
+        Ke 0.5 0.5 0.5
+    ";
+
+    #[test]
+    fn test_furnace() {
+        let tobj = tobj::load_obj_buf(&mut BufReader::new(FURNACE.as_bytes()), |_| {
+            tobj::load_mtl_buf(&mut BufReader::new(FURNACE_MTL.as_bytes()))
+        })
+        .unwrap();
+        let scene = SceneBuilder::new().build_from_tobj(tobj).unwrap();
+        let datastructure = KDTreeDataStructure::new(&scene);
+
+        // Particles that scatter all light (and absorb none) don't change the light either.
+        let shaders: [&dyn Shader; 4] = [
+            &McShader::new(false, RussianRoulette::new(3, 64)),
+            &McShader::new(true, RussianRoulette::new(3, 64)),
+            &VMcShader::new(0.5, 1., false, RussianRoulette::new(3, 64)),
+            &VMcShader::new(0.5, 1., true, RussianRoulette::new(3, 64)),
+        ];
+
+        let ray = Ray::new(Vector::new(0.2, 0.1, 0.), Vector::new(0.3, 0.2, -1.).unit());
+        for shader in &shaders {
+            reseed(3);
+            let count = 4000;
+            let mut total = Vector::repeated(0.);
+            for _ in 0..count {
+                total += shader.shade(&ray, &datastructure, &mut SampleStream::random());
+            }
+
+            // The light bounces on forever: 0.5 + 0.5 * 0.5 + 0.5 * 0.5 * 0.5 + ... = 1.
+            let mean = total / count as f64;
+            assert!((mean - Vector::repeated(1.)).length() < 0.03, "{:?}", mean);
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use crate::datastructure::DataStructure;
+use crate::sampler::SampleStream;
 use crate::shader::shaders::{ambient, diffuse, emittance, specular};
 use crate::shader::Shader;
 use crate::util::ray::Ray;
@@ -8,8 +9,13 @@ use crate::util::vector::Vector;
 pub struct MtlShader;
 
 impl Shader for MtlShader {
-    fn shade<'s>(&self, ray: &Ray, datastructure: &'s (dyn DataStructure + 's)) -> Vector {
-        let intersection = if let Some(intersection) = datastructure.intersects(&ray) {
+    fn shade<'s>(
+        &self,
+        ray: &Ray,
+        datastructure: &'s (dyn DataStructure + 's),
+        _samples: &mut SampleStream,
+    ) -> Vector {
+        let intersection = if let Some(intersection) = datastructure.intersects(ray) {
             intersection
         } else {
             return Vector::repeated(0f64);
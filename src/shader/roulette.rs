@@ -0,0 +1,84 @@
+use crate::sampler::SampleStream;
+use crate::util::vector::Vector;
+
+/// Ends paths at random instead of at a fixed depth (Russian roulette). After `min_depth`
+/// bounces, a path continues with a probability that follows its throughput (how much of the
+/// light at the end of the path still reaches the camera), and a path that continues is
+/// weighted up by one over that probability, so the result stays unbiased. Dim paths end
+/// early, bright interreflections are followed further.
+#[derive(Debug)]
+pub struct RussianRoulette {
+    /// Paths always bounce at least this many times (unless they leave the scene).
+    min_depth: usize,
+    /// Paths never bounce more than this many times. This is the only source of bias left,
+    /// but paths rarely survive this long.
+    max_depth: usize,
+}
+
+impl RussianRoulette {
+    pub fn new(min_depth: usize, max_depth: usize) -> Self {
+        Self {
+            min_depth,
+            max_depth,
+        }
+    }
+
+    /// Whether a path that has bounced `bounce` times can bounce again at all.
+    pub fn can_bounce(&self, bounce: usize) -> bool {
+        bounce < self.max_depth
+    }
+
+    /// Decides whether a path that has bounced `bounce` times with `throughput` bounces
+    /// again. Returns the probability with which it did, to divide the bounce by,
+    /// or None when the path ends here.
+    pub fn survive(
+        &self,
+        bounce: usize,
+        throughput: Vector,
+        samples: &mut SampleStream,
+    ) -> Option<f64> {
+        if !self.can_bounce(bounce) {
+            return None;
+        }
+        if bounce < self.min_depth {
+            return Some(1.);
+        }
+
+        let probability = throughput.max_item().min(1.);
+        if samples.next_1d() < probability {
+            Some(probability)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sampler::SampleStream;
+    use crate::shader::roulette::RussianRoulette;
+    use crate::util::vector::Vector;
+
+    #[test]
+    fn test_survive() {
+        let roulette = RussianRoulette::new(2, 5);
+        let mut samples = SampleStream::random();
+
+        let mut survive =
+            |bounce, value| roulette.survive(bounce, Vector::repeated(value), &mut samples);
+        assert_eq!(survive(1, 0.), Some(1.));
+        assert_eq!(survive(5, 1.), None);
+        assert_eq!(survive(3, 2.), Some(1.));
+        assert_eq!(survive(3, 0.), None);
+
+        // Half of the paths survive, and count double.
+        let throughput = Vector::new(0.5, 0.2, 0.1);
+        let mut total = 0.;
+        for _ in 0..10000 {
+            total += roulette
+                .survive(2, throughput, &mut samples)
+                .map_or(0., |i| 1. / i);
+        }
+        assert!((total / 10000. - 1.).abs() < 0.05);
+    }
+}
@@ -51,7 +51,7 @@ pub fn diffuse(intersection: &Intersection, hit_pos: Vector, light_pos: Vector)
     };
 
     let light_dir = (light_pos - hit_pos).unit();
-    light_dir.dot(triangle.normal()).max(0.) * triangle.material().diffuse * texture
+    light_dir.dot(intersection.normal()).max(0.) * triangle.material().diffuse * texture
 }
 
 pub fn specular(
@@ -71,7 +71,8 @@ pub fn specular(
     let triangle = intersection.triangle;
 
     let light_dir = (light_pos - hit_pos).unit();
-    let reflec = 2f64 * (triangle.normal().dot(light_dir)) * triangle.normal() - light_dir;
+    let normal = intersection.normal();
+    let reflec = 2f64 * (normal.dot(light_dir)) * normal - light_dir;
     let spec = 0f64.max((cam_pos - hit_pos).unit().dot(reflec));
 
     spec.powf(triangle.material().shininess) * triangle.material().specular * texture
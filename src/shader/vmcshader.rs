@@ -1,111 +1,207 @@
+use crate::datastructure::intersection::Intersection;
 use crate::datastructure::DataStructure;
-use crate::shader::shaders::{emittance, map_uv};
+use crate::sampler::SampleStream;
+use crate::shader::bsdf::{bsdf_at, Bsdf};
+use crate::shader::lightsampling::{light_pdf, power_heuristic, sample_light};
+use crate::shader::roulette::RussianRoulette;
+use crate::shader::shaders::emittance;
 use crate::shader::Shader;
 use crate::util::ray::Ray;
-use crate::util::rng::get_rng;
+use crate::util::statistics::{count_path, count_ray, PathEnd, RayType};
 use crate::util::vector::Vector;
-use rand::Rng;
-use std::f64;
+
+/// The dimensions one step of a path takes at most: 4 for the particles in the air,
+/// 3 for light sampling, 1 for the Russian roulette and 3 for the bounce.
+const STEP_DIMENSIONS: usize = 11;
 
 #[derive(Debug)]
 pub struct VMcShader {
     air_density: f64,
     particle_reflectivity: f64,
+    /// Sample light sources directly at every surface bounce (next event estimation).
+    light_sampling: bool,
+    /// Ends paths at surface bounces. Particles end paths by absorbing them.
+    roulette: RussianRoulette,
 }
 
 impl VMcShader {
-    pub fn new(air_density: f64, particle_reflectivity: f64) -> Self {
+    pub fn new(
+        air_density: f64,
+        particle_reflectivity: f64,
+        light_sampling: bool,
+        roulette: RussianRoulette,
+    ) -> Self {
         Self {
             air_density,
             particle_reflectivity,
+            light_sampling,
+            roulette,
         }
     }
 
-    pub fn shade_internal<'a>(
+    /// Follows the path of `ray` bounce by bounce (on surfaces and particles), adding up the
+    /// light that reaches the camera along it. Every step of the path starts at a fixed
+    /// dimension of `samples`, whether it scattered on a particle or bounced on a surface.
+    pub fn trace_path<'a>(
         &self,
         ray: &Ray,
-        depth: usize,
         datastructure: &'a (dyn DataStructure + 'a),
+        samples: &mut SampleStream,
     ) -> Vector {
-        let intersection = if let Some(intersection) = datastructure.intersects(&ray) {
-            intersection
-        } else {
-            return if depth > 0 {
-                let reflec_type = get_rng(|mut r| r.gen::<f64>());
+        let mut ray = ray.clone();
+        let mut radiance = Vector::repeated(0f64);
+        let mut throughput = Vector::repeated(1f64);
+        // The probability density with which the direction of `ray` was sampled at the
+        // previous surface bounce. It is None for camera rays and rays scattered by
+        // particles, whose emission is always counted in full.
+        let mut bsdf_pdf = None;
+        let first_dimension = samples.dimension();
+
+        for bounce in 0.. {
+            samples.skip_to(first_dimension + bounce * STEP_DIMENSIONS);
+            // Taken up front, so the rest of the step gets the same dimensions
+            // whether or not the ray hits a particle.
+            let breakdist = -samples.next_1d().ln() / self.air_density;
+            let reflec_type = samples.next_1d();
+            let scatter_direction = samples.next_2d();
+
+            let intersection = if let Some(intersection) = datastructure.intersects(&ray) {
+                intersection
+            } else {
+                if !self.roulette.can_bounce(bounce) {
+                    count_path(bounce, PathEnd::MaxDepth);
+                    break;
+                }
+
                 if self.particle_reflectivity > reflec_type {
-                    let breakdist = -get_rng(|mut r| r.gen::<f64>()).ln() / self.air_density;
                     let hit_point = ray.origin + ray.direction * breakdist;
-                    let scatter_ray = Ray::new(hit_point, Vector::point_on_sphere());
-                    self.shade_internal(&scatter_ray, depth - 1, datastructure)
+                    let direction = Vector::point_on_sphere(scatter_direction);
+                    ray = Ray::new(hit_point, direction).with_time(ray.time);
+                    bsdf_pdf = None;
+                    count_ray(RayType::Bounce);
+                    continue;
                 } else {
-                    Vector::repeated(0f64)
+                    count_path(bounce, PathEnd::Escaped);
+                    break;
                 }
-            } else {
-                Vector::repeated(0f64)
             };
-        };
-
-        let hit_pos = intersection.hit_pos();
-        let dist = (ray.origin - hit_pos).length();
-        let breakdist = -get_rng(|mut r| r.gen::<f64>()).ln() / self.air_density;
-
-        if breakdist < dist {
-            let reflec_type = get_rng(|mut r| r.gen::<f64>());
-            if self.particle_reflectivity > reflec_type {
-                let hit_point = ray.origin + ray.direction * breakdist;
-                let scatter_ray = Ray::new(hit_point, Vector::point_on_sphere());
-                if depth > 0 {
-                    return self.shade_internal(&scatter_ray, depth - 1, datastructure);
+
+            let hit_pos = intersection.hit_pos();
+            let dist = (ray.origin - hit_pos).length();
+
+            if breakdist < dist {
+                if self.particle_reflectivity > reflec_type {
+                    let hit_point = ray.origin + ray.direction * breakdist;
+                    let direction = Vector::point_on_sphere(scatter_direction);
+                    if self.roulette.can_bounce(bounce) {
+                        ray = Ray::new(hit_point, direction).with_time(ray.time);
+                        bsdf_pdf = None;
+                        count_ray(RayType::Bounce);
+                        continue;
+                    } else {
+                        count_path(bounce, PathEnd::MaxDepth);
+                        break;
+                    }
                 } else {
-                    return Vector::repeated(0f64);
+                    count_path(bounce, PathEnd::Absorbed);
+                    break;
                 }
-            } else {
-                return Vector::repeated(0f64);
             }
-        }
-        //
-        //        let part_amb = ambient(&intersection.face, self.scene) * Vector::repeated(0.1);
-        let part_emi = emittance(&intersection);
-
-        //        let part_diff = diffuse(&intersection.face, self.scene, hit_pos, pointlight) * brightness;
-        //        let part_spec = specular(&intersection.face, self.scene, hit_pos, pointlight, intersection.ray.origin) * brightness;
-        //
-        //
-        //        let direct = part_amb + part_emi + part_diff + part_spec;
-
-        let indirect = if depth > 0 {
-            let reflec_type = get_rng(|mut r| r.gen::<f64>());
-            let diffuse_max = intersection.triangle.material().diffuse.max_item();
-            if diffuse_max > reflec_type {
-                let fliped_normal = if intersection.triangle.normal().dot(ray.direction) < 0. {
-                    intersection.triangle.normal()
+
+            radiance += throughput
+                * emittance(&intersection)
+                * self.emittance_weight(&intersection, bsdf_pdf);
+
+            if !self.roulette.can_bounce(bounce) {
+                count_path(bounce, PathEnd::MaxDepth);
+                break;
+            }
+
+            let bsdf = bsdf_at(&intersection);
+            let outgoing = ray.direction * -1.;
+
+            if self.light_sampling {
+                radiance += throughput
+                    * self.direct_light(
+                        &intersection,
+                        &bsdf,
+                        hit_pos,
+                        outgoing,
+                        datastructure,
+                        samples,
+                    );
+            }
+
+            let survival =
+                if let Some(survival) = self.roulette.survive(bounce, throughput, samples) {
+                    survival
                 } else {
-                    intersection.triangle.normal() * -1.
+                    count_path(bounce, PathEnd::Roulette);
+                    break;
                 };
-                let bounce_direction = Vector::point_on_diffuse_hemisphere().rotated(fliped_normal);
-                let bounce_ray = Ray::new(hit_pos, bounce_direction);
-                let indirect_light = self.shade_internal(&bounce_ray, depth - 1, datastructure);
-                let texture =
-                    if let Some(texture) = intersection.triangle.mesh.material.diffuse_texture {
-                        let coord = map_uv(&intersection);
-                        texture.at(coord)
-                    } else {
-                        Vector::new(1., 1., 1.)
-                    };
-                indirect_light * intersection.triangle.material().diffuse / diffuse_max * texture
+
+            let pick = samples.next_1d();
+            let point = samples.next_2d();
+            let sample = if let Some(sample) = bsdf.sample(outgoing, pick, point) {
+                sample
             } else {
-                Vector::repeated(0f64)
-            }
-        } else {
-            Vector::repeated(0f64)
-        };
+                count_path(bounce, PathEnd::Absorbed);
+                break;
+            };
+
+            throughput = throughput * sample.weight / survival;
+            bsdf_pdf = Some(sample.pdf);
+            ray = Ray::new(hit_pos, sample.direction).with_time(ray.time);
+            count_ray(RayType::Bounce);
+        }
 
-        indirect + part_emi
+        radiance
+    }
+
+    /// The MIS weight of light that a surface bounce found by chance. Without light sampling,
+    /// bounces are the only way to find light, so it counts in full.
+    fn emittance_weight(&self, intersection: &Intersection, bsdf_pdf: Option<f64>) -> f64 {
+        match bsdf_pdf {
+            Some(pdf) if self.light_sampling => power_heuristic(pdf, light_pdf(intersection)),
+            _ => 1.,
+        }
+    }
+
+    /// Light arriving at `hit_pos` directly from a sampled point on a light source,
+    /// attenuated by the particles in the air along the way.
+    fn direct_light(
+        &self,
+        intersection: &Intersection,
+        bsdf: &dyn Bsdf,
+        hit_pos: Vector,
+        outgoing: Vector,
+        datastructure: &dyn DataStructure,
+        samples: &mut SampleStream,
+    ) -> Vector {
+        let sample =
+            if let Some(sample) = sample_light(intersection, hit_pos, datastructure, samples) {
+                sample
+            } else {
+                return Vector::repeated(0f64);
+            };
+
+        // A bounce towards the light would only reach it without hitting a particle first.
+        let transmittance = (-self.air_density * sample.distance).exp();
+        let weight = power_heuristic(sample.pdf, bsdf.pdf(outgoing, sample.direction));
+
+        sample.emittance
+            * bsdf.eval(outgoing, sample.direction)
+            * (transmittance * weight / sample.pdf)
     }
 }
 
 impl Shader for VMcShader {
-    fn shade<'s>(&self, ray: &Ray, datastructure: &'s (dyn DataStructure + 's)) -> Vector {
-        self.shade_internal(ray, 6, datastructure)
+    fn shade<'s>(
+        &self,
+        ray: &Ray,
+        datastructure: &'s (dyn DataStructure + 's),
+        samples: &mut SampleStream,
+    ) -> Vector {
+        self.trace_path(ray, datastructure, samples)
     }
 }
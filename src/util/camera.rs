@@ -1,11 +1,92 @@
+use crate::sampler::SampleStream;
 use crate::util::ray::Ray;
 use crate::util::vector::Vector;
 use std::f64;
 
+/// A thin lens in front of the camera. Only things at the focus distance are sharp,
+/// everything else gets blurrier the larger the aperture is.
+#[derive(Debug)]
+pub struct ThinLens {
+    pub aperture_radius: f64,
+    pub focus_distance: f64,
+    /// The number of aperture blades. Out of focus highlights get the shape of a polygon
+    /// with this many corners. None (or less than 3) gives a round aperture.
+    pub blades: Option<usize>,
+}
+
+impl ThinLens {
+    /// Maps two uniform numbers in [0, 1) to a uniformly distributed point on the aperture,
+    /// relative to its center.
+    fn sample_aperture(&self, (u, v): (f64, f64)) -> (f64, f64) {
+        let (x, y) = match self.blades {
+            Some(blades) if blades >= 3 => {
+                // A point in one of the triangles between the center and two neighbouring corners.
+                // Which triangle is picked by u, and the rest of u says where in it.
+                let u = u * blades as f64;
+                let triangle = (u as usize).min(blades - 1);
+                let w = u - triangle as f64;
+                let corner_angle = 2. * f64::consts::PI / blades as f64;
+                let a = triangle as f64 * corner_angle;
+                let b = a + corner_angle;
+
+                let scale = v.sqrt();
+                (
+                    scale * ((1. - w) * a.cos() + w * b.cos()),
+                    scale * ((1. - w) * a.sin() + w * b.sin()),
+                )
+            }
+            _ => {
+                let radius = u.sqrt();
+                let angle = 2. * f64::consts::PI * v;
+                (radius * angle.cos(), radius * angle.sin())
+            }
+        };
+
+        (x * self.aperture_radius, y * self.aperture_radius)
+    }
+}
+
+/// The interval in which the shutter is open. Every ray is given a random time in it,
+/// so meshes that move during the exposure get motion blur.
+#[derive(Debug, Clone, Copy)]
+pub struct Shutter {
+    pub open: f64,
+    pub close: f64,
+}
+
+impl Shutter {
+    fn sample_time(&self, u: f64) -> f64 {
+        self.open + u * (self.close - self.open)
+    }
+}
+
+/// How directions in the scene are mapped onto the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// A pinhole camera. Straight lines stay straight.
+    Perspective,
+    /// All rays are parallel to the camera direction, so sizes don't shrink with distance.
+    /// `height` is the height of the view in scene units.
+    Orthographic { height: f64 },
+    /// An equidistant fisheye: the angle between a ray and the camera direction grows
+    /// linearly with the distance of its pixel from the center of the image.
+    /// The field of view is the angle from the top to the bottom of the image, and can be
+    /// up to 360 degrees.
+    Fisheye,
+    /// A 360 degree panorama. The width of the image covers all directions around the up
+    /// vector and the height goes from straight up to straight down, so it ignores the
+    /// field of view. Use an image that is twice as wide as it is high.
+    Equirectangular,
+}
+
 #[derive(Debug)]
 pub struct Camera {
     pub pos: Vector,
-    pub direction: Vector,
+    /// The orthonormal basis of the camera: it looks along `forward`,
+    /// with `right` and `up` pointing to the right and the top of the image.
+    pub forward: Vector,
+    pub right: Vector,
+    pub up: Vector,
     pub width: usize,
     pub height: usize,
     pub fov: f64,
@@ -13,19 +94,38 @@ pub struct Camera {
     pub inf_height: f64,
     pub angle: f64,
     pub aspect_ratio: f64,
+    pub projection: Projection,
+    /// Without a lens, the camera is a pinhole camera and everything is sharp.
+    pub lens: Option<ThinLens>,
+    /// Without a shutter, every ray is cast at time 0.
+    pub shutter: Option<Shutter>,
 }
 
 impl Camera {
-    pub fn new(pos: Vector, direction: Vector, width: usize, height: usize, fov: f64) -> Self {
+    /// A camera at `pos` looking in `direction`. The top of the image points
+    /// towards `up` as much as possible, it doesn't have to be perpendicular to `direction`.
+    /// `fov` is the vertical field of view in degrees.
+    pub fn new(
+        pos: Vector,
+        direction: Vector,
+        up: Vector,
+        width: usize,
+        height: usize,
+        fov: f64,
+    ) -> Self {
         let inf_width = 1f64 / (width as f64);
         let inf_height = 1f64 / (height as f64);
         let angle = (f64::consts::PI * 0.5f64 * fov / 180f64).tan();
         let aspect_ratio = width as f64 / height as f64;
-        let internal_direction = direction.rotated(Vector::new(0., 0., 1.)).unit();
+
+        let forward = direction.unit();
+        let right = Self::right_vector(forward, up);
 
         Self {
             pos,
-            direction: internal_direction,
+            forward,
+            right,
+            up: right.cross(forward),
             width,
             height,
             fov,
@@ -33,21 +133,332 @@ impl Camera {
             inf_height,
             angle,
             aspect_ratio,
+            projection: Projection::Perspective,
+            lens: None,
+            shutter: None,
+        }
+    }
+
+    /// A camera at `pos` looking at `target`.
+    pub fn look_at(
+        pos: Vector,
+        target: Vector,
+        up: Vector,
+        width: usize,
+        height: usize,
+        fov: f64,
+    ) -> Self {
+        Self::new(pos, target - pos, up, width, height, fov)
+    }
+
+    /// Points right in the image, perpendicular to `forward`. When `up` is parallel to
+    /// `forward`, the world axis that is furthest from `forward` is used as up instead.
+    fn right_vector(forward: Vector, up: Vector) -> Vector {
+        let right = forward.cross(up);
+        if right.length() > 1e-9 {
+            return right.unit();
+        }
+
+        let axis = if forward.x.abs() < forward.y.abs() && forward.x.abs() < forward.z.abs() {
+            Vector::new(1., 0., 0.)
+        } else if forward.y.abs() < forward.z.abs() {
+            Vector::new(0., 1., 0.)
+        } else {
+            Vector::new(0., 0., 1.)
+        };
+        forward.cross(axis).unit()
+    }
+
+    /// Rotates the camera around its direction by `degrees`, counter clockwise
+    /// as seen from behind the camera. So the image turns clockwise.
+    pub fn with_roll(mut self, degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let right = self.right * cos + self.up * sin;
+        let up = self.up * cos - self.right * sin;
+
+        self.right = right;
+        self.up = up;
+        self
+    }
+
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    pub fn with_lens(mut self, lens: ThinLens) -> Self {
+        self.lens = Some(lens);
+        self
+    }
+
+    pub fn with_shutter(mut self, shutter: Shutter) -> Self {
+        self.shutter = Some(shutter);
+        self
+    }
+
+    /// Converts a vector from camera space (x right, y up, looking along -z) to world space.
+    fn to_world(&self, vector: Vector) -> Vector {
+        self.right * vector.x + self.up * vector.y - self.forward * vector.z
+    }
+
+    /// The ray through (x, y) of a pinhole camera with this projection. The direction is
+    /// one unit long along the camera axis for perspective cameras and one unit long otherwise,
+    /// so `origin + direction * d` is always on the focal surface at distance d.
+    /// None when the projection doesn't cover (x, y).
+    fn pinhole_ray(&self, x: f64, y: f64) -> Option<(Vector, Vector)> {
+        // From -1 on the left/bottom edge to 1 on the right/top edge
+        let u = 2f64 * x * self.inf_width - 1f64;
+        let v = 1f64 - 2f64 * y * self.inf_height;
+
+        match self.projection {
+            Projection::Perspective => {
+                let xdir = u * self.angle * self.aspect_ratio;
+                let ydir = v * self.angle;
+
+                Some((self.pos, self.to_world(Vector::new(xdir, ydir, -1f64))))
+            }
+            Projection::Orthographic { height } => {
+                let xoffset = u * self.aspect_ratio * height * 0.5;
+                let yoffset = v * height * 0.5;
+
+                Some((
+                    self.pos + self.to_world(Vector::new(xoffset, yoffset, 0.)),
+                    self.forward,
+                ))
+            }
+            Projection::Fisheye => {
+                let x = u * self.aspect_ratio;
+                let radius = (x * x + v * v).sqrt();
+                // The image circle touches the top and bottom of the image, the field of
+                // view is measured across it. Nothing is seen outside of it.
+                if radius > 1. {
+                    return None;
+                }
+                let theta = radius * self.fov.to_radians() * 0.5;
+
+                let direction = if radius > 0. {
+                    let scale = theta.sin() / radius;
+                    Vector::new(x * scale, v * scale, -theta.cos())
+                } else {
+                    Vector::new(0., 0., -1.)
+                };
+                Some((self.pos, self.to_world(direction)))
+            }
+            Projection::Equirectangular => {
+                let longitude = u * f64::consts::PI;
+                let latitude = v * f64::consts::FRAC_PI_2;
+
+                let direction = Vector::new(
+                    latitude.cos() * longitude.sin(),
+                    latitude.sin(),
+                    -latitude.cos() * longitude.cos(),
+                );
+                Some((self.pos, self.to_world(direction)))
+            }
+        }
+    }
+
+    /// The ray through (x, y) on the image. The point on the lens and the time
+    /// are taken from `samples`. None where the camera sees nothing, like outside the
+    /// image circle of a fisheye. Those parts of the image are black.
+    pub fn generate_ray(&self, x: f64, y: f64, samples: &mut SampleStream) -> Option<Ray> {
+        // The samples are taken first, so the shader gets the same dimensions either way.
+        let lens_sample = samples.next_2d();
+        let time = self
+            .shutter
+            .map_or(0., |i| i.sample_time(samples.next_1d()));
+        let (origin, raydir) = self.pinhole_ray(x, y)?;
+
+        let ray = match &self.lens {
+            None => Ray::new(origin, raydir.unit()),
+            Some(lens) => {
+                // This is where the ray through the center of the lens meets the focal plane.
+                let focus_point = origin + raydir * lens.focus_distance;
+                let (u, v) = lens.sample_aperture(lens_sample);
+                let origin = origin + self.to_world(Vector::new(u, v, 0.));
+
+                Ray::new(origin, (focus_point - origin).unit())
+            }
+        };
+
+        Some(ray.with_time(time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sampler::SampleStream;
+    use crate::util::camera::{Camera, Projection, ThinLens};
+    use crate::util::ray::Ray;
+    use crate::util::vector::Vector;
+
+    const UP: Vector = Vector {
+        x: 0.,
+        y: 1.,
+        z: 0.,
+    };
+
+    fn ray_through(camera: &Camera, x: f64, y: f64) -> Ray {
+        camera
+            .generate_ray(x, y, &mut SampleStream::random())
+            .unwrap()
+    }
+
+    fn looking(direction: Vector) -> Camera {
+        Camera::new(Vector::repeated(0.), direction, UP, 100, 100, 90.)
+    }
+
+    fn assert_close(a: Vector, b: Vector) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_directions() {
+        // A 90 degree fov puts the edges of the image at 45 degrees.
+        let camera = looking(Vector::new(0., 0., -1.));
+
+        assert_close(
+            ray_through(&camera, 50., 50.).direction,
+            Vector::new(0., 0., -1.),
+        );
+        assert_close(
+            ray_through(&camera, 100., 50.).direction,
+            Vector::new(1., 0., -1.).unit(),
+        );
+        assert_close(
+            ray_through(&camera, 50., 0.).direction,
+            Vector::new(0., 1., -1.).unit(),
+        );
+
+        let camera = looking(Vector::new(1., 0., 0.));
+        assert_close(camera.right, Vector::new(0., 0., 1.));
+        assert_close(
+            ray_through(&camera, 100., 50.).direction,
+            Vector::new(1., 0., 1.).unit(),
+        );
+
+        // The up vector doesn't need to be perpendicular to the direction.
+        let camera = looking(Vector::new(0., -1., -1.));
+        assert_close(camera.up, Vector::new(0., 1., -1.).unit());
+        assert_close(camera.right, Vector::new(1., 0., 0.));
+    }
+
+    #[test]
+    fn test_look_at() {
+        let camera = Camera::look_at(
+            Vector::new(0., 0., 5.),
+            Vector::new(0., 0., 0.),
+            Vector::new(0., 0., 1.),
+            100,
+            100,
+            90.,
+        );
+
+        let ray = ray_through(&camera, 50., 50.);
+        assert_close(ray.origin, Vector::new(0., 0., 5.));
+        assert_close(ray.direction, Vector::new(0., 0., -1.));
+
+        // Up is parallel to the direction, but the basis is still orthonormal.
+        assert!(camera.right.dot(camera.up).abs() < 1e-9);
+        assert!(camera.right.dot(camera.forward).abs() < 1e-9);
+        assert!((camera.right.length() - 1.).abs() < 1e-9);
+        assert!((camera.up.length() - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_roll() {
+        let camera = looking(Vector::new(0., 0., -1.)).with_roll(90.);
+
+        // The top of the image now points left.
+        assert_close(camera.up, Vector::new(-1., 0., 0.));
+        assert_close(
+            ray_through(&camera, 50., 0.).direction,
+            Vector::new(-1., 0., -1.).unit(),
+        );
+    }
+
+    #[test]
+    fn test_lens_focus() {
+        let position = Vector::new(1., 2., 3.);
+        let direction = Vector::new(0.3, -0.2, -1.);
+        let pinhole = ray_through(
+            &Camera::new(position, direction, UP, 100, 100, 60.),
+            20.,
+            70.,
+        );
+
+        for blades in &[None, Some(6)] {
+            let camera = Camera::new(position, direction, UP, 100, 100, 60.).with_lens(ThinLens {
+                aperture_radius: 0.5,
+                focus_distance: 4.,
+                blades: *blades,
+            });
+            // The focal plane is perpendicular to the direction of the camera.
+            let focus_point =
+                position + pinhole.direction * (4. / pinhole.direction.dot(direction.unit()));
+
+            for _ in 0..100 {
+                let ray = ray_through(&camera, 20., 70.);
+                assert!((ray.origin - position).length() <= 0.5 + 1e-9);
+                assert!((ray.origin - position).dot(direction).abs() < 1e-9);
+
+                // Every ray through the lens passes through the same point on the focal plane.
+                assert_close((focus_point - ray.origin).unit(), ray.direction);
+            }
         }
     }
 
-    pub fn generate_ray(&self, x: f64, y: f64) -> Ray {
-        let xdir = (2f64 * x as f64 * self.inf_width - 1f64) * self.angle * self.aspect_ratio;
-        let ydir = (1f64 - 2f64 * y as f64 * self.inf_height) * self.angle;
+    #[test]
+    fn test_projections() {
+        let camera = looking(Vector::new(0., 0., -1.))
+            .with_projection(Projection::Orthographic { height: 4. });
+        let ray = ray_through(&camera, 100., 0.);
+        assert_close(ray.origin, Vector::new(2., 2., 0.));
+        assert_close(ray.direction, Vector::new(0., 0., -1.));
 
-        let raydir = Vector::new(xdir, ydir, -1f64)
-            .rotated(Vector::new(0., 0., 1.))
-            .rotated(self.direction)
-            .rotated(Vector::new(0., 0., -1.));
-        // raydir = raydir.rotated(Vector::new(0.,0.,1.)).rotated(Vector::new(-1.,0.,0.)).rotated(Vector::new(0.,0.,-1.));
-        // raydir = raydir.rotated(Vector::new(0., 1., -0.35).unit());
-//        raydir.normalize();
+        // With a 180 degree fisheye, the edges of the image look sideways.
+        let camera = Camera::new(
+            Vector::repeated(0.),
+            Vector::new(0., 0., -1.),
+            UP,
+            100,
+            100,
+            180.,
+        )
+        .with_projection(Projection::Fisheye);
+        assert_close(
+            ray_through(&camera, 50., 50.).direction,
+            Vector::new(0., 0., -1.),
+        );
+        assert_close(
+            ray_through(&camera, 100., 50.).direction,
+            Vector::new(1., 0., 0.),
+        );
+        assert_close(
+            ray_through(&camera, 50., 25.).direction,
+            Vector::new(0., 1., -1.).unit(),
+        );
+        // The corners are outside the image circle.
+        assert!(camera
+            .generate_ray(2., 2., &mut SampleStream::random())
+            .is_none());
 
-        Ray::new(self.pos, raydir)
+        let camera = looking(Vector::new(0., 0., -1.)).with_projection(Projection::Equirectangular);
+        assert_close(
+            ray_through(&camera, 50., 50.).direction,
+            Vector::new(0., 0., -1.),
+        );
+        assert_close(
+            ray_through(&camera, 75., 50.).direction,
+            Vector::new(1., 0., 0.),
+        );
+        assert_close(
+            ray_through(&camera, 0., 50.).direction,
+            Vector::new(0., 0., 1.),
+        );
+        assert_close(
+            ray_through(&camera, 50., 0.).direction,
+            Vector::new(0., 1., 0.),
+        );
     }
 }
@@ -4,9 +4,3 @@ pub struct Color {
     pub g: u8,
     pub b: u8,
 }
-
-impl Color {
-    pub fn new(r: u8, g: u8, b: u8) -> Self {
-        Self { r, g, b }
-    }
-}
@@ -0,0 +1,180 @@
+use crate::filter::Filter;
+use crate::util::outputbuffer::OutputBuffer;
+use crate::util::vector::Vector;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// The number of bits after the point of the fixed point sums.
+const FRACTION_BITS: i32 = 24;
+
+/// The largest contribution a single sample can make to a sum, so that infinite samples
+/// can't overflow the sums of a pixel.
+const MAX_CONTRIBUTION: f64 = (1u64 << 40) as f64;
+
+/// Collects the samples of a render. Every sample is added to all pixels whose filter covers
+/// it, weighted by the filter, and every pixel ends up as the weighted average of the samples
+/// around it.
+///
+/// Samples of neighbouring pixels are added from different threads, so the sums are kept in
+/// fixed point: unlike floats, the result doesn't depend on the order of the additions, and
+/// seeded renders stay the same whatever the generator.
+pub struct Film {
+    width: usize,
+    height: usize,
+    /// Per pixel the weighted red, green and blue, the total weight and the total absolute
+    /// weight of its samples.
+    sums: Vec<[AtomicI64; 5]>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            sums: (0..width * height).map(|_| Default::default()).collect(),
+        }
+    }
+
+    /// Adds a sample at `offset` from the center of pixel (x, y) to every pixel within the
+    /// radius of `filter`. A pixel covers [-0.5, 0.5) around its center, so the filter covers
+    /// [-radius, radius) and a box of radius 0.5 adds every sample to exactly one pixel.
+    pub fn add_sample(
+        &self,
+        x: usize,
+        y: usize,
+        offset: (f64, f64),
+        color: Vector,
+        filter: &dyn Filter,
+    ) {
+        let radius = filter.radius();
+        // The pixels from `min` up to (and including) `max` along one axis.
+        let range = |position: usize, offset: f64, size: usize| {
+            let min = (position as f64 + offset - radius).floor().max(0.) as usize;
+            let max = (position as f64 + offset + radius).ceil().max(0.) as usize;
+            min..=max.min(size - 1)
+        };
+
+        for other_y in range(y, offset.1, self.height) {
+            let distance_y = y as f64 + offset.1 - other_y as f64;
+            if distance_y < -radius || distance_y >= radius {
+                continue;
+            }
+
+            for other_x in range(x, offset.0, self.width) {
+                let distance_x = x as f64 + offset.0 - other_x as f64;
+                if distance_x < -radius || distance_x >= radius {
+                    continue;
+                }
+
+                let weight = filter.evaluate(distance_x, distance_y);
+                if weight != 0. {
+                    self.add(other_x, other_y, color, weight);
+                }
+            }
+        }
+    }
+
+    /// Adds a sample to pixel (x, y) only, with a weight of 1.
+    pub fn add_pixel_sample(&self, x: usize, y: usize, color: Vector) {
+        self.add(x, y, color, 1.);
+    }
+
+    fn add(&self, x: usize, y: usize, color: Vector, weight: f64) {
+        let sums = &self.sums[y * self.width + x];
+        let values = [
+            color.x * weight,
+            color.y * weight,
+            color.z * weight,
+            weight,
+            weight.abs(),
+        ];
+
+        for (sum, value) in sums.iter().zip(values.iter()) {
+            sum.fetch_add(to_fixed(*value), Ordering::Relaxed);
+        }
+    }
+
+    /// The weighted average of the samples of every pixel. Filters with negative lobes can
+    /// leave a pixel with almost no weight when the positive and negative weights cancel out,
+    /// which would make it arbitrarily bright. So the weight a pixel is divided by is at least
+    /// half of its total absolute weight, which keeps it within twice its brightest sample.
+    pub fn to_output(&self) -> OutputBuffer {
+        let mut output = OutputBuffer::with_size(self.width, self.height);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let sums = &self.sums[y * self.width + x];
+                let [r, g, b, weight, absolute_weight] =
+                    [0, 1, 2, 3, 4].map(|i| from_fixed(sums[i].load(Ordering::Relaxed)));
+
+                if absolute_weight > 0. {
+                    let weight = weight.signum() * weight.abs().max(absolute_weight / 2.);
+                    output.set_at(x, y, Vector::new(r, g, b) / weight);
+                }
+            }
+        }
+
+        output
+    }
+}
+
+fn to_fixed(value: f64) -> i64 {
+    // NaN becomes 0 here.
+    (value * (1u64 << FRACTION_BITS) as f64).clamp(-MAX_CONTRIBUTION, MAX_CONTRIBUTION) as i64
+}
+
+fn from_fixed(value: i64) -> f64 {
+    value as f64 / (1u64 << FRACTION_BITS) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filter::boxfilter::BoxFilter;
+    use crate::filter::lanczos::Lanczos;
+    use crate::filter::tent::Tent;
+    use crate::util::film::Film;
+    use crate::util::vector::Vector;
+
+    #[test]
+    fn test_add_sample() {
+        // A box of half a pixel averages the samples of every pixel, also those on its edge.
+        let film = Film::new(3, 1);
+        let filter = BoxFilter::new(0.5);
+        film.add_sample(1, 0, (-0.5, 0.), Vector::repeated(1.), &filter);
+        film.add_sample(1, 0, (0.25, 0.), Vector::repeated(3.), &filter);
+        let output = film.to_output();
+        assert_eq!(output[0][0], Vector::repeated(0.));
+        assert_eq!(output[0][1], Vector::repeated(2.));
+        assert_eq!(output[0][2], Vector::repeated(0.));
+
+        // A tent of a pixel spreads a sample between two pixels over both.
+        let film = Film::new(3, 1);
+        let filter = Tent::new(1.);
+        film.add_sample(0, 0, (0.25, 0.), Vector::repeated(1.), &filter);
+        film.add_sample(2, 0, (0., 0.), Vector::repeated(4.), &filter);
+        let output = film.to_output();
+        assert_eq!(output[0][0], Vector::repeated(1.));
+        assert_eq!(output[0][1], Vector::repeated(1.));
+        assert_eq!(output[0][2], Vector::repeated(4.));
+    }
+
+    #[test]
+    fn test_negative_weights() {
+        // A pixel that only gets a sample in a negative lobe of its filter still gets its color.
+        let film = Film::new(3, 1);
+        let filter = Lanczos::new(3., 3.);
+        film.add_sample(0, 0, (0.4, 0.), Vector::repeated(1.), &filter);
+        for pixel in &film.to_output()[0] {
+            assert!((pixel.x - 1.).abs() < 1e-6, "{}", pixel.x);
+        }
+
+        // Pixel 0 gets a black sample with a weight of -0.147 and five white ones with 0.031,
+        // which almost cancel out. Divided by their total weight, it would be about 18.
+        let film = Film::new(3, 1);
+        film.add_sample(1, 0, (0.4, 0.), Vector::repeated(0.), &filter);
+        for _ in 0..5 {
+            film.add_sample(2, 0, (0.3, 0.), Vector::repeated(1.), &filter);
+        }
+        let pixel = film.to_output()[0][0];
+        assert!(pixel.x > 0. && pixel.x <= 2., "{}", pixel.x);
+    }
+}
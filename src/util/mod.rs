@@ -1,7 +1,9 @@
 pub mod camera;
 pub mod color;
 pub mod consts;
+pub mod film;
 pub mod outputbuffer;
 pub mod ray;
 pub mod rng;
+pub mod statistics;
 pub mod vector;
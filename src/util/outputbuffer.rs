@@ -1,8 +1,12 @@
+use crate::output::error::OutputError;
+use crate::output::{ImageFormat, OutputOptions};
 use crate::util::color::Color;
 use crate::util::vector::Vector;
-use bmp::{px, Image, Pixel};
+use image::{ImageBuffer, Rgb, RgbImage};
 use std::ops::{Deref, DerefMut};
+use std::path::Path;
 
+#[derive(Default, Clone)]
 pub struct OutputBuffer {
     buffer: Vec<Vec<Vector>>,
 }
@@ -41,27 +45,94 @@ impl OutputBuffer {
         res
     }
 
-    pub fn to_bmp(&self) -> Image {
+    fn size(&self) -> (u32, u32) {
         let height = self.buffer.len();
         let width = if height > 0 { self.buffer[0].len() } else { 0 };
 
-        let mut img = Image::new(width as u32, height as u32);
+        (width as u32, height as u32)
+    }
 
-        for (x, y) in img.coordinates() {
-            let color: &Color = &self.buffer[y as usize][x as usize].into();
-            img.set_pixel(x, y, px!(color.r, color.g, color.b));
-        }
+    /// Converts to 8 bits per channel, clamping the colors to [0, 1].
+    pub fn to_rgb8(&self) -> RgbImage {
+        let (width, height) = self.size();
+
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let color: Color = self.buffer[y as usize][x as usize].into();
+            Rgb([color.r, color.g, color.b])
+        })
+    }
+
+    /// Converts to 16 bits per channel, clamping the colors to [0, 1].
+    pub fn to_rgb16(&self) -> ImageBuffer<Rgb<u16>, Vec<u16>> {
+        let (width, height) = self.size();
 
-        img
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let color = self.buffer[y as usize][x as usize];
+            Rgb([color.x, color.y, color.z].map(|i| (i.clamp(0., 1.) * 65535.) as u16))
+        })
+    }
+
+    /// Saves the buffer in the format that matches the extension of `filename`.
+    /// Float formats (pfm, hdr and exr) keep the linear values as they are.
+    pub fn save(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &OutputOptions,
+    ) -> Result<(), OutputError> {
+        let filename = filename.as_ref();
+        ImageFormat::from_path(filename)?.write(self, filename, options)
     }
 
     pub fn set_at(&mut self, x: usize, y: usize, color: Vector) {
         self.buffer[y][x] = color;
     }
+
+    /// Applies `f` to every pixel.
+    pub fn map_pixels(mut self, f: impl Fn(Vector) -> Vector) -> Self {
+        for pixel in self.buffer.iter_mut().flatten() {
+            *pixel = f(*pixel);
+        }
+
+        self
+    }
+
+    /// Treats this buffer as the average of `count` earlier buffers, and adds `other`
+    /// to that average. Both buffers must have the same size.
+    pub fn average_in(&mut self, other: &OutputBuffer, count: usize) {
+        let count = count as f64;
+
+        for (row, other_row) in self.buffer.iter_mut().zip(other.iter()) {
+            for (pixel, other_pixel) in row.iter_mut().zip(other_row.iter()) {
+                *pixel = (*pixel * count + *other_pixel) / (count + 1.);
+            }
+        }
+    }
 }
 
-impl Default for OutputBuffer {
-    fn default() -> Self {
-        Self { buffer: Vec::new() }
+#[cfg(test)]
+mod tests {
+    use crate::util::outputbuffer::OutputBuffer;
+    use crate::util::vector::Vector;
+
+    #[test]
+    fn test_average_in() {
+        let mut average = OutputBuffer::with_size(2, 1);
+
+        for (count, value) in [3., 6., 0.].iter().enumerate() {
+            let mut pass = OutputBuffer::with_size(2, 1);
+            pass.set_at(0, 0, Vector::repeated(*value));
+            average.average_in(&pass, count);
+        }
+
+        assert_eq!(average[0][0], Vector::repeated(3.));
+        assert_eq!(average[0][1], Vector::repeated(0.));
+    }
+
+    #[test]
+    fn test_to_rgb16() {
+        let mut buffer = OutputBuffer::with_size(1, 1);
+        buffer.set_at(0, 0, Vector::new(-1., 0.5, 3.));
+
+        assert_eq!(buffer.to_rgb16().get_pixel(0, 0).0, [0, 32767, 65535]);
     }
 }
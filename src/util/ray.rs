@@ -1,13 +1,25 @@
 use crate::util::vector::Vector;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Ray {
     pub origin: Vector,
     pub direction: Vector,
+    /// The moment during the exposure at which the ray travels. Moving meshes are
+    /// intersected where they are at this time.
+    pub time: f64,
 }
 
 impl Ray {
     pub fn new(origin: Vector, direction: Vector) -> Self {
-        Self { origin, direction }
+        Self {
+            origin,
+            direction,
+            time: 0.,
+        }
+    }
+
+    pub fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
     }
 }
@@ -14,3 +14,19 @@ pub fn get_rng<T>(mut func: impl FnMut(RefMut<RngType>) -> T) -> T {
         func(r)
     })
 }
+
+/// Restarts the random number generator of the current thread from `seed`, so everything
+/// drawn from it afterwards only depends on the seed and not on what ran on the thread before.
+pub fn reseed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = RngType::seed_from_u64(seed))
+}
+
+/// Combines a seed with a value (like a pass or pixel number) into a new seed. Seeds that
+/// differ in a single value still give unrelated sequences.
+pub fn mix_seed(seed: u64, value: u64) -> u64 {
+    // The finalizer of splitmix64
+    let mut z = seed ^ value.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
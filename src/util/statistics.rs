@@ -0,0 +1,332 @@
+use serde::Serialize;
+use std::cell::RefCell;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Paths are counted by their number of bounces up to this depth. Longer paths
+/// are counted in the last bucket.
+const PATH_DEPTH_BUCKETS: usize = 65;
+
+/// Ray and path counts, by `RayType`, depth and `PathEnd`.
+struct Counts<T> {
+    rays: [T; 3],
+    path_depths: [T; PATH_DEPTH_BUCKETS],
+    path_ends: [T; 4],
+}
+
+impl<T> Counts<T> {
+    /// All counts, in the same order for every `Counts`.
+    fn iter(&self) -> impl Iterator<Item = &T> {
+        self.rays
+            .iter()
+            .chain(self.path_depths.iter())
+            .chain(self.path_ends.iter())
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.rays
+            .iter_mut()
+            .chain(self.path_depths.iter_mut())
+            .chain(self.path_ends.iter_mut())
+    }
+}
+
+impl Counts<u64> {
+    fn new() -> Self {
+        Self {
+            rays: [0; 3],
+            path_depths: [0; PATH_DEPTH_BUCKETS],
+            path_ends: [0; 4],
+        }
+    }
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU64 = AtomicU64::new(0);
+
+/// The counts of all threads together, since the last `reset_ray_counts`.
+static TOTALS: Counts<AtomicU64> = Counts {
+    rays: [ZERO; 3],
+    path_depths: [ZERO; PATH_DEPTH_BUCKETS],
+    path_ends: [ZERO; 4],
+};
+
+thread_local! {
+    /// Every thread counts on its own, so threads don't all write the same memory for every
+    /// ray. The counts are added to the totals by `flush_counts`.
+    static COUNTS: RefCell<Counts<u64>> = RefCell::new(Counts::new());
+}
+
+pub enum RayType {
+    /// A ray coming from the camera.
+    Primary,
+    /// A ray continuing a path after it bounced off a surface or scattered on a particle.
+    Bounce,
+    /// A ray testing whether a light source is visible.
+    Shadow,
+}
+
+/// Counts a ray that was cast.
+pub fn count_ray(raytype: RayType) {
+    COUNTS.with(|counts| counts.borrow_mut().rays[raytype as usize] += 1);
+}
+
+pub enum PathEnd {
+    /// The path left the scene.
+    Escaped,
+    /// A surface or a particle in the air absorbed the path.
+    Absorbed,
+    /// Russian roulette ended the path.
+    Roulette,
+    /// The path bounced the maximum number of times.
+    MaxDepth,
+}
+
+/// Counts a path from the camera that ended after `depth` bounces.
+pub fn count_path(depth: usize, end: PathEnd) {
+    COUNTS.with(|counts| {
+        let mut counts = counts.borrow_mut();
+        counts.path_depths[depth.min(PATH_DEPTH_BUCKETS - 1)] += 1;
+        counts.path_ends[end as usize] += 1;
+    });
+}
+
+/// Adds the counts of the current thread to the totals of the whole program, which
+/// `RenderStatistics::new` reports. The generators call this after every pixel.
+pub fn flush_counts() {
+    COUNTS.with(|counts| {
+        for (count, total) in counts.borrow_mut().iter_mut().zip(TOTALS.iter()) {
+            if *count > 0 {
+                total.fetch_add(*count, Ordering::Relaxed);
+                *count = 0;
+            }
+        }
+    });
+}
+
+/// Sets all ray and path counts back to zero.
+pub fn reset_ray_counts() {
+    for total in TOTALS.iter() {
+        total.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Why the paths of a render ended.
+#[derive(Serialize, Debug)]
+pub struct PathEnds {
+    pub escaped: u64,
+    pub absorbed: u64,
+    pub roulette: u64,
+    pub max_depth: u64,
+}
+
+/// Timings and ray counts of a render. All times are in seconds.
+#[derive(Serialize, Debug)]
+pub struct RenderStatistics {
+    pub scene_load_time: f64,
+    pub datastructure_build_time: f64,
+    pub render_time: f64,
+
+    pub primary_rays: u64,
+    pub bounce_rays: u64,
+    pub shadow_rays: u64,
+
+    pub rays_per_second: f64,
+    /// The average number of rays (primary and bounces) that make up a path from the camera.
+    pub average_path_length: f64,
+
+    /// The number of paths that ended after 0, 1, 2, ... bounces. The last entry also
+    /// counts longer paths. Empty for shaders that don't trace paths.
+    pub path_depths: Vec<u64>,
+    pub path_ends: PathEnds,
+}
+
+impl RenderStatistics {
+    /// Collects the statistics of a render, taking the ray counts since the last
+    /// `reset_ray_counts`.
+    pub fn new(
+        scene_load_time: Duration,
+        datastructure_build_time: Duration,
+        render_time: Duration,
+    ) -> Self {
+        let mut counts = Counts::new();
+        for (count, total) in counts.iter_mut().zip(TOTALS.iter()) {
+            *count = total.load(Ordering::Relaxed);
+        }
+
+        Self::from_counts(
+            scene_load_time,
+            datastructure_build_time,
+            render_time,
+            &counts,
+        )
+    }
+
+    fn from_counts(
+        scene_load_time: Duration,
+        datastructure_build_time: Duration,
+        render_time: Duration,
+        counts: &Counts<u64>,
+    ) -> Self {
+        let [primary_rays, bounce_rays, shadow_rays] = counts.rays;
+        let [escaped, absorbed, roulette, max_depth] = counts.path_ends;
+
+        let mut path_depths = counts.path_depths.to_vec();
+        while path_depths.last() == Some(&0) {
+            path_depths.pop();
+        }
+
+        let render_time = render_time.as_secs_f64();
+        let total_rays = primary_rays + bounce_rays + shadow_rays;
+
+        Self {
+            scene_load_time: scene_load_time.as_secs_f64(),
+            datastructure_build_time: datastructure_build_time.as_secs_f64(),
+            render_time,
+            primary_rays,
+            bounce_rays,
+            shadow_rays,
+            rays_per_second: if render_time > 0. {
+                total_rays as f64 / render_time
+            } else {
+                0.
+            },
+            average_path_length: if primary_rays > 0 {
+                (primary_rays + bounce_rays) as f64 / primary_rays as f64
+            } else {
+                0.
+            },
+            path_depths,
+            path_ends: PathEnds {
+                escaped,
+                absorbed,
+                roulette,
+                max_depth,
+            },
+        }
+    }
+}
+
+impl Display for RenderStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Scene load time:          {:.3}s", self.scene_load_time)?;
+        writeln!(
+            f,
+            "Datastructure build time: {:.3}s",
+            self.datastructure_build_time
+        )?;
+        writeln!(f, "Render time:              {:.3}s", self.render_time)?;
+        writeln!(f, "Primary rays:             {}", self.primary_rays)?;
+        writeln!(f, "Bounce rays:              {}", self.bounce_rays)?;
+        writeln!(f, "Shadow rays:              {}", self.shadow_rays)?;
+        writeln!(f, "Rays per second:          {:.0}", self.rays_per_second)?;
+        write!(
+            f,
+            "Average path length:      {:.2}",
+            self.average_path_length
+        )?;
+
+        if self.path_depths.is_empty() {
+            return Ok(());
+        }
+
+        let ends = &self.path_ends;
+        write!(
+            f,
+            "\nPath ends:                {} escaped, {} absorbed, {} roulette, {} max depth",
+            ends.escaped, ends.absorbed, ends.roulette, ends.max_depth
+        )?;
+        write!(f, "\nPath depths:")?;
+        let total: u64 = self.path_depths.iter().sum();
+        for (depth, &count) in self.path_depths.iter().enumerate() {
+            let more = if depth == PATH_DEPTH_BUCKETS - 1 {
+                "+"
+            } else {
+                ""
+            };
+            write!(
+                f,
+                "\n  {:>3}{:<1} {:>10} {:>6.2}%",
+                depth,
+                more,
+                count,
+                count as f64 / total as f64 * 100.
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::statistics::{
+        count_path, count_ray, flush_counts, Counts, PathEnd, RayType, RenderStatistics, COUNTS,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_counts_per_thread() {
+        count_ray(RayType::Primary);
+        count_ray(RayType::Bounce);
+        count_ray(RayType::Bounce);
+        count_path(2, PathEnd::Roulette);
+        count_path(100, PathEnd::MaxDepth);
+
+        COUNTS.with(|counts| {
+            let counts = counts.borrow();
+            assert_eq!(counts.rays, [1, 2, 0]);
+            assert_eq!(counts.path_depths[2], 1);
+            assert_eq!(counts.path_depths[64], 1);
+            assert_eq!(counts.path_ends, [0, 0, 1, 1]);
+        });
+
+        flush_counts();
+        COUNTS.with(|counts| assert!(counts.borrow().iter().all(|&count| count == 0)));
+    }
+
+    #[test]
+    fn test_render_statistics() {
+        let mut counts = Counts::new();
+        counts.rays = [100, 150, 50];
+        counts.path_depths[0] = 40;
+        counts.path_depths[3] = 60;
+        counts.path_ends = [70, 10, 15, 5];
+
+        let statistics = RenderStatistics::from_counts(
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            Duration::from_secs(3),
+            &counts,
+        );
+
+        assert_eq!(statistics.render_time, 3.);
+        assert_eq!(statistics.rays_per_second, 100.);
+        assert_eq!(statistics.average_path_length, 2.5);
+        assert_eq!(statistics.path_depths, vec![40, 0, 0, 60]);
+        assert_eq!(statistics.path_ends.roulette, 15);
+
+        let text = statistics.to_string();
+        assert!(text.contains("70 escaped, 10 absorbed, 15 roulette, 5 max depth"));
+        assert!(text.contains("  3          60  60.00%"));
+    }
+
+    #[test]
+    fn test_render_statistics_without_paths() {
+        let mut counts = Counts::new();
+        counts.rays = [10, 0, 0];
+
+        let statistics = RenderStatistics::from_counts(
+            Duration::from_secs(0),
+            Duration::from_secs(0),
+            Duration::from_secs(0),
+            &counts,
+        );
+
+        assert_eq!(statistics.rays_per_second, 0.);
+        assert_eq!(statistics.average_path_length, 1.);
+        assert!(statistics.path_depths.is_empty());
+        assert!(!statistics.to_string().contains("Path ends"));
+    }
+}
@@ -1,6 +1,4 @@
 use crate::util::color::Color;
-use crate::util::rng::get_rng;
-use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::f64;
 use std::ops::{Add, AddAssign, Div, Mul, Sub};
@@ -13,7 +11,7 @@ trait Clamp01 {
 
 impl Clamp01 for f64 {
     fn clamp01(self) -> Self {
-        self.min(1.).max(0.)
+        self.clamp(0., 1.)
     }
 }
 
@@ -110,8 +108,9 @@ impl Vector {
         }
     }
 
-    pub fn gamma(&self, exp: f64) -> Vector {
-        self.powf(exp) * (exp + 1f64) / 2f64
+    /// The relative luminance of a linear rec. 709 (and sRGB) color.
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
     }
 
     pub fn rotated(&self, rotation: Vector) -> Vector {
@@ -132,9 +131,11 @@ impl Vector {
         Vector::new(x, y, z)
     }
 
-    pub fn point_on_hemisphere() -> Vector {
-        let theta = get_rng(|mut r| r.gen::<f64>()) * 2f64 * f64::consts::PI;
-        let phi = (1f64 - 2f64 * get_rng(|mut r| r.gen::<f64>())).acos();
+    /// A uniformly distributed direction on the hemisphere around +y,
+    /// from two uniform numbers in [0, 1).
+    pub fn point_on_hemisphere((u, v): (f64, f64)) -> Vector {
+        let theta = u * 2f64 * f64::consts::PI;
+        let phi = (1f64 - 2f64 * v).acos();
 
         Vector::new(
             phi.sin() * theta.cos(),
@@ -143,9 +144,10 @@ impl Vector {
         )
     }
 
-    pub fn point_on_sphere() -> Vector {
-        let theta = get_rng(|mut r| r.gen::<f64>()) * 2f64 * f64::consts::PI;
-        let phi = (1f64 - 2f64 * get_rng(|mut r| r.gen::<f64>())).acos();
+    /// A uniformly distributed direction, from two uniform numbers in [0, 1).
+    pub fn point_on_sphere((u, v): (f64, f64)) -> Vector {
+        let theta = u * 2f64 * f64::consts::PI;
+        let phi = (1f64 - 2f64 * v).acos();
 
         Vector::new(
             phi.sin() * theta.cos(),
@@ -154,20 +156,21 @@ impl Vector {
         )
     }
 
-    pub fn point_on_diffuse_hemisphere() -> Vector {
-        let u = get_rng(|mut r| r.gen::<f64>());
-        let v = 2. * f64::consts::PI * get_rng(|mut r| r.gen::<f64>());
+    /// A cosine weighted direction on the hemisphere around +y,
+    /// from two uniform numbers in [0, 1).
+    pub fn point_on_diffuse_hemisphere((u, v): (f64, f64)) -> Vector {
+        let v = 2. * f64::consts::PI * v;
 
         Vector::new(v.cos() * u.sqrt(), (1. - u).sqrt(), v.sin() * u.sqrt())
     }
 }
 
-impl Into<Color> for Vector {
-    fn into(self) -> Color {
+impl From<Vector> for Color {
+    fn from(val: Vector) -> Self {
         Color {
-            r: (self.x.clamp01() * 255.) as u8,
-            g: (self.y.clamp01() * 255.) as u8,
-            b: (self.z.clamp01() * 255.) as u8,
+            r: (val.x.clamp01() * 255.) as u8,
+            g: (val.y.clamp01() * 255.) as u8,
+            b: (val.z.clamp01() * 255.) as u8,
         }
     }
 }